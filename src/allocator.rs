@@ -0,0 +1,232 @@
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    hint,
+    ptr::{addr_of_mut, null_mut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::arch::mmap::{self, MAP_ANONYMOUS, MAP_PRIVATE, PROT_READ, PROT_WRITE};
+
+/// Our own allocator, there is no libc `malloc` underneath us.
+///
+/// It only ever uses `mmap`, never `brk`, so the program's heap is left to the program.
+#[cfg_attr(not(test), global_allocator)]
+static ALLOCATOR: ArenaAllocator = ArenaAllocator;
+
+/// The smallest page x86_64 has, `mmap` lengths only need to be a multiple of it.
+/// NOTE: Not `page_size::get_page_size`, we allocate before it's known.
+const PAGE_SIZE: usize = 4096;
+
+/// Small allocations are carved out of arenas, each one holding blocks of a single size:
+///
+/// ```text
+/// ┌--------------┬---------┬---------┬-----┬-----------------┐
+/// | Arena header | block 0 | block 1 | ... | not handed out  |
+/// └--------------┴---------┴---------┴-----┴-----------------┘
+/// ^ aligned to ARENA_SIZE, so a block's arena is its address rounded down
+/// ```
+const ARENA_SIZE: usize = 64 * 1024;
+/// Block sizes are powers of two from 16 up to this, anything bigger gets a mapping of its own.
+const LARGEST_BLOCK: usize = 2048;
+const SIZE_CLASSES: usize =
+    (LARGEST_BLOCK.trailing_zeros() - 16usize.trailing_zeros() + 1) as usize;
+
+#[repr(C)]
+struct Arena {
+    /// The next arena with blocks of the same size.
+    next: *mut Arena,
+    /// Freed blocks, each one starts with a pointer to the next.
+    free: *mut FreeBlock,
+    /// Where the blocks that were never handed out start.
+    unused: usize,
+    /// How many blocks are handed out, the arena can be unmapped once none are.
+    used: usize,
+}
+
+struct FreeBlock {
+    next: *mut FreeBlock,
+}
+
+/// The arenas of each size class, only touched while `LOCK` is held.
+static mut ARENAS: [*mut Arena; SIZE_CLASSES] = [null_mut(); SIZE_CLASSES];
+/// A spin lock of its own: it has to work before thread local storage does.
+static LOCK: AtomicBool = AtomicBool::new(false);
+
+struct Guard;
+
+impl Guard {
+    fn lock() -> Self {
+        while LOCK
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+        Guard
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        LOCK.store(false, Ordering::Release);
+    }
+}
+
+pub struct ArenaAllocator;
+
+/// The size class a layout goes in, `None` if it gets a mapping of its own.
+fn size_class(layout: &Layout) -> Option<usize> {
+    let block_size = layout
+        .size()
+        .max(layout.align())
+        .max(16)
+        .next_power_of_two();
+    if block_size > LARGEST_BLOCK {
+        return None;
+    }
+    Some((block_size.trailing_zeros() - 16usize.trailing_zeros()) as usize)
+}
+
+/// Maps `size` bytes aligned to `align`, by mapping more and unmapping what is left over on either side.
+unsafe fn map_aligned(size: usize, align: usize) -> *mut u8 {
+    let size = size.next_multiple_of(PAGE_SIZE);
+    let extra = align.saturating_sub(PAGE_SIZE);
+    let Ok(mapping) = mmap::mmap(
+        null_mut(),
+        size + extra,
+        PROT_READ | PROT_WRITE,
+        MAP_PRIVATE | MAP_ANONYMOUS,
+        -1,
+        0,
+    ) else {
+        return null_mut();
+    };
+    if extra == 0 {
+        return mapping;
+    }
+
+    let start = mapping.map_addr(|address| address.next_multiple_of(align));
+    let before = start.addr() - mapping.addr();
+    if before != 0 {
+        let _ = mmap::munmap(mapping, before);
+    }
+    if extra - before != 0 {
+        let _ = mmap::munmap(start.byte_add(size), extra - before);
+    }
+    start
+}
+
+unsafe fn allocate_block(class: usize) -> *mut u8 {
+    let block_size = 16 << class;
+    // Blocks after the header, aligned to their own size:
+    let first_block = size_of::<Arena>().next_multiple_of(block_size);
+
+    let mut arena = ARENAS[class];
+    while !arena.is_null() {
+        let free = (*arena).free;
+        if !free.is_null() {
+            (*arena).free = (*free).next;
+            (*arena).used += 1;
+            return free.cast();
+        }
+        if (*arena).unused + block_size <= ARENA_SIZE {
+            let block = arena.byte_add((*arena).unused);
+            (*arena).unused += block_size;
+            (*arena).used += 1;
+            return block.cast();
+        }
+        arena = (*arena).next;
+    }
+
+    // Every arena is full:
+    let arena = map_aligned(ARENA_SIZE, ARENA_SIZE) as *mut Arena;
+    if arena.is_null() {
+        return null_mut();
+    }
+    arena.write(Arena {
+        next: ARENAS[class],
+        free: null_mut(),
+        unused: first_block + block_size,
+        used: 1,
+    });
+    ARENAS[class] = arena;
+    arena.byte_add(first_block).cast()
+}
+
+unsafe fn free_block(block: *mut u8) {
+    let arena = block.map_addr(|address| address & !(ARENA_SIZE - 1)) as *mut Arena;
+    let block = block as *mut FreeBlock;
+    (*block).next = (*arena).free;
+    (*arena).free = block;
+    (*arena).used -= 1;
+}
+
+unsafe impl GlobalAlloc for ArenaAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match size_class(&layout) {
+            Some(class) => {
+                let _guard = Guard::lock();
+                allocate_block(class)
+            }
+            None => map_aligned(layout.size(), layout.align()),
+        }
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        match size_class(&layout) {
+            Some(_) => {
+                let _guard = Guard::lock();
+                free_block(pointer);
+            }
+            None => {
+                let _ = mmap::munmap(pointer, layout.size().next_multiple_of(PAGE_SIZE));
+            }
+        }
+    }
+
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        // Still fits the block (or the pages) it's in:
+        let fits = match (size_class(&layout), size_class(&new_layout)) {
+            (Some(class), Some(new_class)) => class == new_class,
+            (None, None) => {
+                layout.size().next_multiple_of(PAGE_SIZE) == new_size.next_multiple_of(PAGE_SIZE)
+            }
+            _ => false,
+        };
+        if fits {
+            return pointer;
+        }
+
+        let new_pointer = self.alloc(new_layout);
+        if !new_pointer.is_null() {
+            core::ptr::copy_nonoverlapping(pointer, new_pointer, layout.size().min(new_size));
+            self.dealloc(pointer, layout);
+        }
+        new_pointer
+    }
+}
+
+/// Hands arenas nothing is allocated from back to the kernel, returns how many bytes that freed.
+///
+/// Meant for once startup is done, most of what the loader allocated along the way is gone by then.
+pub fn release_unused() -> usize {
+    let _guard = Guard::lock();
+    let mut released = 0;
+    unsafe {
+        for head in (*addr_of_mut!(ARENAS)).iter_mut() {
+            let mut link: *mut *mut Arena = head;
+            while !(*link).is_null() {
+                let arena = *link;
+                if (*arena).used == 0 {
+                    *link = (*arena).next;
+                    let _ = mmap::munmap(arena.cast(), ARENA_SIZE);
+                    released += ARENA_SIZE;
+                } else {
+                    link = addr_of_mut!((*arena).next);
+                }
+            }
+        }
+    }
+    released
+}
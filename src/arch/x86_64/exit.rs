@@ -1,8 +1,4 @@
-use std::arch::asm;
-
-const CODE_ADDEND: usize = 22200;
-
-pub const EXIT_UNKNOWN_RELOCATION: usize = CODE_ADDEND + 1;
+use core::arch::asm;
 
 #[inline(always)]
 pub fn exit(code: usize) -> ! {
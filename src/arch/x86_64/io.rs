@@ -1,26 +1,33 @@
-use std::arch::asm;
-
-use crate::syscall_debug_assert;
+use super::syscall::{self, EINTR};
 
 pub const STD_IN: i32 = 0;
 pub const STD_OUT: i32 = 1;
 pub const STD_ERR: i32 = 2;
 
+/// Writes all of `s`, or as much as the file descriptor takes before failing.
+///
+/// NOTE: Errors are ignored, there is nowhere left to report them.
 #[inline(always)]
 pub fn write(fd: i32, s: &str) {
-    const WRITE: usize = 1;
+    let mut remaining = s.as_bytes();
+    while !remaining.is_empty() {
+        // A pipe or a terminal can take less than all of it:
+        match syscall::write(fd, remaining) {
+            Ok(0) => return,
+            Ok(written) => remaining = &remaining[written..],
+            // Interrupted before anything was written, try again:
+            Err(EINTR) => continue,
+            Err(_) => return,
+        }
+    }
+}
+
+/// Whether `fd` is a terminal, the same check `isatty` does.
+#[inline(always)]
+pub fn is_terminal(fd: i32) -> bool {
+    const TCGETS: usize = 0x5401;
 
-    let result: isize;
-    unsafe {
-        asm!(
-            "syscall",
-            inlateout("rax") WRITE => result,
-            in("rdi") fd,
-            in("rsi") s.as_ptr(),
-            in("rdx") s.len(),
-            out("rcx") _,
-            out("r11") _,
-            options(nostack)
-        )
-    };
+    // Only asked for so the kernel has somewhere to put it, `struct termios` is 60 bytes:
+    let mut termios = [0u8; 60];
+    unsafe { syscall::ioctl(fd, TCGETS, termios.as_mut_ptr().cast()) }.is_ok()
 }
@@ -0,0 +1,90 @@
+//! The C functions the compiler emits calls to (and `core` links against), there's no libc to provide them.
+//!
+//! NOTE: These are all string instructions, never plain loops; the compiler recognizes loops that copy, fill, compare or
+//! measure bytes and turns them back into calls to these very functions. They also get called before we are relocated.
+
+use core::arch::asm;
+
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn memcpy(destination: *mut u8, source: *const u8, length: usize) -> *mut u8 {
+    asm!(
+        "rep movsb",
+        inout("rcx") length => _,
+        inout("rdi") destination => _,
+        inout("rsi") source => _,
+        options(nostack, preserves_flags)
+    );
+    destination
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn memmove(
+    destination: *mut u8,
+    source: *const u8,
+    length: usize,
+) -> *mut u8 {
+    if destination.addr().wrapping_sub(source.addr()) >= length {
+        // The destination doesn't start inside the source, copying forwards never overwrites what's still to be copied:
+        return memcpy(destination, source, length);
+    }
+    // Otherwise copy backwards, from the last byte:
+    asm!(
+        "std",
+        "rep movsb",
+        "cld",
+        inout("rcx") length => _,
+        inout("rdi") destination.add(length).wrapping_sub(1) => _,
+        inout("rsi") source.add(length).wrapping_sub(1) => _,
+        options(nostack)
+    );
+    destination
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn memset(destination: *mut u8, value: i32, length: usize) -> *mut u8 {
+    asm!(
+        "rep stosb",
+        inout("rcx") length => _,
+        inout("rdi") destination => _,
+        in("al") value as u8,
+        options(nostack, preserves_flags)
+    );
+    destination
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn memcmp(left: *const u8, right: *const u8, length: usize) -> i32 {
+    if length == 0 {
+        return 0;
+    }
+    // Compares until the first difference (or the end), leaving both pointers just past the last pair it compared:
+    let (left_end, right_end): (*const u8, *const u8);
+    asm!(
+        "repe cmpsb",
+        inout("rcx") length => _,
+        inout("rsi") left => left_end,
+        inout("rdi") right => right_end,
+        options(nostack, readonly)
+    );
+    *left_end.sub(1) as i32 - *right_end.sub(1) as i32
+}
+
+/// `memcmp` for when only equality matters.
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn bcmp(left: *const u8, right: *const u8, length: usize) -> i32 {
+    memcmp(left, right, length)
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn strlen(string: *const u8) -> usize {
+    // Counts down from `usize::MAX` until it has scanned past the null byte:
+    let remaining: usize;
+    asm!(
+        "repne scasb",
+        inout("rcx") usize::MAX => remaining,
+        inout("rdi") string => _,
+        in("al") 0u8,
+        options(nostack, readonly)
+    );
+    !remaining - 1
+}
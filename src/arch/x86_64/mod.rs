@@ -4,10 +4,16 @@ use crate::{io_macros::*, syscall_debug_assert};
 
 pub mod exit;
 pub mod io;
+pub mod memory;
+pub use exit::exit;
+pub use io::write;
 pub mod mmap;
 pub mod relocation;
+pub mod syscall;
 pub mod thread_pointer;
+pub mod time;
 
+#[cfg(not(test))]
 #[naked]
 #[no_mangle]
 pub unsafe extern "C" fn _start() -> ! {
@@ -19,3 +25,39 @@ pub unsafe extern "C" fn _start() -> ! {
         sym crate::rust_main,
     );
 }
+
+// Unit tests are an ordinary `std` program, but we link without the C runtime's start files (see `.cargo/config.toml`).
+// So they get the bare minimum of them: an entry point that hands over to `libc` the way `crt1.o` does, and the symbols
+// `crti.o` and `crtbegin.o` would have provided.
+
+#[cfg(test)]
+#[naked]
+#[no_mangle]
+pub unsafe extern "C" fn _start() -> ! {
+    naked_asm!(
+        "xor ebp, ebp",
+        "mov r9, rdx",  // rtld_fini
+        "pop rsi",      // argc
+        "mov rdx, rsp", // argv
+        "and rsp, -16",
+        "push rax",
+        "push rsp", // stack_end
+        "xor r8d, r8d",
+        "xor ecx, ecx",
+        "lea rdi, [rip + main]",
+        "call __libc_start_main",
+        "hlt",
+    );
+}
+
+#[cfg(test)]
+#[no_mangle]
+extern "C" fn _init() {}
+
+#[cfg(test)]
+#[no_mangle]
+extern "C" fn _fini() {}
+
+#[cfg(test)]
+#[no_mangle]
+static __dso_handle: usize = 0;
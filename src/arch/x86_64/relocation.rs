@@ -1,68 +1,92 @@
-use std::arch::asm;
+use core::arch::asm;
 
-use crate::{elf::relocate::Relocatable, syscall_assert};
+use crate::{elf::relocate::Relocatable, error::LoaderError};
 
-pub unsafe fn relocate(object: &impl Relocatable) {
-    let relocation_slices = object.relocation_slices();
-    // Variables in relocation formulae:
-    // - A(rela.r_addend): This is the addend used to compute the value of the relocatable field.
-    // - B(self.base.addr): This is the base address at which a shared object has been loaded into memory during execution.
-    // - G(??): This is the offset into the global offset table at which the address of the relocation entry’s symbol will reside during execution.
-    // - GOT(global_offset_table_address): This is the address of the global offset table.
-    // - L(??): ??
-    // - P(relocate_address): This is the address of the storage unit being relocated.
-    // - S(self.symbol.st_value): This is the value of the symbol table entry indexed at `rela.r_sym()`.
-    //   NOTE: In the ELF specification `S` is equal to (symbol.st_value + base_address) but that doesn't make any sense to me.
-    // - Z(??): ??
+// Variables in relocation formulae:
+// - A(rela.r_addend): This is the addend used to compute the value of the relocatable field.
+// - B(self.base.addr): This is the base address at which a shared object has been loaded into memory during execution.
+// - G(??): This is the offset into the global offset table at which the address of the relocation entry’s symbol will reside during execution.
+// - GOT(global_offset_table_address): This is the address of the global offset table.
+// - L(??): ??
+// - P(relocate_address): This is the address of the storage unit being relocated.
+// - S(self.symbol.st_value): This is the value of the symbol table entry indexed at `rela.r_sym()`.
+//   NOTE: In the ELF specification `S` is equal to (symbol.st_value + base_address) but that doesn't make any sense to me.
+// - Z(??): ??
+
+// x86_64 relocation types:
+/// | None
+pub const R_X86_64_NONE: u32 = 0;
+/// S + B + A | u64
+pub const R_X86_64_64: u32 = 1;
+/// S + B + A - P | u32
+pub const R_X86_64_PC32: u32 = 2;
+/// G + A | u32
+pub const R_X86_64_GOT32: u32 = 3;
+/// L + A - P | u32
+pub const R_X86_64_PLT32: u32 = 4;
+/// | None
+pub const R_X86_64_COPY: u32 = 5;
+/// S + B | u64
+pub const R_X86_64_GLOB_DAT: u32 = 6;
+/// S + B | u64
+pub const R_X86_64_JUMP_SLOT: u32 = 7;
+/// B + A | u64
+pub const R_X86_64_RELATIVE: u32 = 8;
+/// G + GOT + A - P | u32
+pub const R_X86_64_GOTPCREL: u32 = 9;
+/// S + B + A | u32
+pub const R_X86_64_32: u32 = 10;
+/// S + B + A | u32
+pub const R_X86_64_32S: u32 = 11;
+/// S + B + A | u16
+pub const R_X86_64_16: u32 = 12;
+/// S + B + A - P | u16
+pub const R_X86_64_PC16: u32 = 13;
+/// S + B + A | u8
+pub const R_X86_64_8: u32 = 14;
+/// S + B + A - P | u8
+pub const R_X86_64_PC8: u32 = 15;
+/// S + B + A - P | u64
+pub const R_X86_64_PC64: u32 = 24;
+/// S + B + A - GOT | u64
+pub const R_X86_64_GOTOFF64: u32 = 25;
+/// GOT + A - P | u32
+pub const R_X86_64_GOTPC32: u32 = 26;
+/// Z + A | u32
+pub const R_X86_64_SIZE32: u32 = 32;
+/// Z + A | u64
+pub const R_X86_64_SIZE64: u32 = 33;
+/// The returned value from the function located at (B + A) | u64
+pub const R_X86_64_IRELATIVE: u32 = 37; // This one is fucking awesome... I mean, it's a little annoying but really cool.
 
-    // x86_64 relocation types:
-    /// | None
-    const R_X86_64_NONE: u32 = 0;
-    /// S + B + A | u64
-    const R_X86_64_64: u32 = 1;
-    /// S + B + A - P | u32
-    const R_X86_64_PC32: u32 = 2;
-    /// G + A | u32
-    const R_X86_64_GOT32: u32 = 3;
-    /// L + A - P | u32
-    const R_X86_64_PLT32: u32 = 4;
-    /// | None
-    const R_X86_64_COPY: u32 = 5;
-    /// S + B | u64
-    const R_X86_64_GLOB_DAT: u32 = 6;
-    /// S + B | u64
-    const R_X86_64_JUMP_SLOT: u32 = 7;
-    /// B + A | u64
-    const R_X86_64_RELATIVE: u32 = 8;
-    /// G + GOT + A - P | u32
-    const R_X86_64_GOTPCREL: u32 = 9;
-    /// S + B + A | u32
-    const R_X86_64_32: u32 = 10;
-    /// S + B + A | u32
-    const R_X86_64_32S: u32 = 11;
-    /// S + B + A | u16
-    const R_X86_64_16: u32 = 12;
-    /// S + B + A - P | u16
-    const R_X86_64_PC16: u32 = 13;
-    /// S + B + A | u8
-    const R_X86_64_8: u32 = 14;
-    /// S + B + A - P | u8
-    const R_X86_64_PC8: u32 = 15;
-    /// S + B + A - P | u64
-    const R_X86_64_PC64: u32 = 24;
-    /// S + B + A - GOT | u64
-    const R_X86_64_GOTOFF64: u32 = 25;
-    /// GOT + A - P | u32
-    const R_X86_64_GOTPC32: u32 = 26;
-    /// Z + A | u32
-    const R_X86_64_SIZE32: u32 = 32;
-    /// Z + A | u64
-    const R_X86_64_SIZE64: u32 = 33;
-    /// The returned value from the function located at (B + A) | u64
-    const R_X86_64_IRELATIVE: u32 = 37; // This one is fucking awesome... I mean, it's a little annoying but really cool.
+// You may notice some are missing values; those are part of the Thread-Local Storage ABI see "ELF Handling for Thread-Local Storage":
+pub const R_X86_64_DTPMOD64: u32 = 16;
+
+/// The name of relocation type `r_type`, for diagnostics; `None` for types we don't know.
+///
+/// NOTE: The names may end up in a table of pointers, which is only usable once we are relocated.
+pub fn relocation_name(r_type: u32) -> Option<&'static str> {
+    let name = match r_type {
+        R_X86_64_NONE => "R_X86_64_NONE",
+        R_X86_64_64 => "R_X86_64_64",
+        R_X86_64_PC32 => "R_X86_64_PC32",
+        R_X86_64_COPY => "R_X86_64_COPY",
+        R_X86_64_GLOB_DAT => "R_X86_64_GLOB_DAT",
+        R_X86_64_JUMP_SLOT => "R_X86_64_JUMP_SLOT",
+        R_X86_64_RELATIVE => "R_X86_64_RELATIVE",
+        R_X86_64_32 => "R_X86_64_32",
+        R_X86_64_32S => "R_X86_64_32S",
+        R_X86_64_DTPMOD64 => "R_X86_64_DTPMOD64",
+        R_X86_64_IRELATIVE => "R_X86_64_IRELATIVE",
+        _ => return None,
+    };
+    Some(name)
+}
 
-    // You may notice some are missing values; those are part of the Thread-Local Storage ABI see "ELF Handling for Thread-Local Storage":
-    const R_X86_64_DTPMOD64: u32 = 16;
+pub unsafe fn relocate(object: &impl Relocatable) {
+    let relocation_slices = object.relocation_slices();
+    // Reported once everything else is applied, so the error can name the type (see `relocation_name`):
+    let mut unsupported = None;
 
     for rela in relocation_slices.rela_slice {
         let relocate_address = rela.r_offset.wrapping_add(object.base().addr());
@@ -118,9 +142,17 @@ pub unsafe fn relocate(object: &impl Relocatable) {
                     options(nostack, preserves_flags),
                 );
             }
-            _ => {
-                syscall_assert!(false, "unsupported relocation");
+            r_type => {
+                unsupported.get_or_insert(r_type);
             }
         }
     }
+
+    if let Some(relocation_type) = unsupported {
+        LoaderError::UnsupportedRelocation {
+            path: "miros",
+            relocation_type,
+        }
+        .exit();
+    }
 }
@@ -0,0 +1,366 @@
+use core::{
+    arch::asm,
+    ffi::CStr,
+    ptr::{null, null_mut},
+};
+
+use super::time::TimeSpec;
+
+/// An error number the kernel returned, what C keeps in `errno`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Errno(pub i32);
+
+// The `errno`s we care about, named like C does:
+pub const EPERM: Errno = Errno(1);
+pub const ENOENT: Errno = Errno(2);
+pub const EINTR: Errno = Errno(4);
+pub const EIO: Errno = Errno(5);
+pub const ENOEXEC: Errno = Errno(8);
+pub const EBADF: Errno = Errno(9);
+pub const EAGAIN: Errno = Errno(11);
+pub const ENOMEM: Errno = Errno(12);
+pub const EACCES: Errno = Errno(13);
+pub const EFAULT: Errno = Errno(14);
+pub const EEXIST: Errno = Errno(17);
+pub const ENOTDIR: Errno = Errno(20);
+pub const EISDIR: Errno = Errno(21);
+pub const EINVAL: Errno = Errno(22);
+pub const ENFILE: Errno = Errno(23);
+pub const EMFILE: Errno = Errno(24);
+pub const ENOTTY: Errno = Errno(25);
+pub const ENAMETOOLONG: Errno = Errno(36);
+pub const ENOSYS: Errno = Errno(38);
+pub const ELOOP: Errno = Errno(40);
+
+/// Relative paths in `openat` and friends are relative to the working directory.
+pub const AT_FDCWD: i32 = -100;
+
+// `openat` flags:
+pub const O_RDONLY: i32 = 0x0;
+pub const O_WRONLY: i32 = 0x1;
+pub const O_CREAT: i32 = 0o100;
+pub const O_TRUNC: i32 = 0o1000;
+pub const O_APPEND: i32 = 0o2000;
+pub const O_NOFOLLOW: i32 = 0o400000;
+pub const O_CLOEXEC: i32 = 0o2000000;
+
+/// What `fstat` fills in, laid out like the kernel's `struct stat` on x86_64.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct Stat {
+    pub st_dev: u64,
+    pub st_ino: u64,
+    pub st_nlink: u64,
+    pub st_mode: u32,
+    pub st_uid: u32,
+    pub st_gid: u32,
+    __pad0: i32,
+    pub st_rdev: u64,
+    pub st_size: i64,
+    pub st_blksize: i64,
+    pub st_blocks: i64,
+    pub st_atime: i64,
+    pub st_atime_nsec: i64,
+    pub st_mtime: i64,
+    pub st_mtime_nsec: i64,
+    pub st_ctime: i64,
+    pub st_ctime_nsec: i64,
+    __unused: [i64; 3],
+}
+
+/// The kernel's `struct sigaction`, which isn't glibc's: the mask is last and only 64 signals wide.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct SigAction {
+    pub sa_handler: usize,
+    pub sa_flags: u64,
+    pub sa_restorer: usize,
+    pub sa_mask: u64,
+}
+
+// System call numbers:
+const READ: usize = 0;
+const WRITE: usize = 1;
+const CLOSE: usize = 3;
+const FSTAT: usize = 5;
+const MMAP: usize = 9;
+const MPROTECT: usize = 10;
+const MUNMAP: usize = 11;
+const RT_SIGACTION: usize = 13;
+const IOCTL: usize = 16;
+const PREAD64: usize = 17;
+const GETPID: usize = 39;
+const READLINK: usize = 89;
+const PRCTL: usize = 157;
+const ARCH_PRCTL: usize = 158;
+const CLOCK_GETTIME: usize = 228;
+const OPENAT: usize = 257;
+const NEWFSTATAT: usize = 262;
+const GETRANDOM: usize = 318;
+
+/// Turns what the kernel returned into a result, `-4095..=-1` are errors.
+#[inline(always)]
+fn result(value: isize) -> Result<usize, Errno> {
+    if (-4095..0).contains(&value) {
+        Err(Errno(-value as i32))
+    } else {
+        Ok(value as usize)
+    }
+}
+
+/// NOTE: Everything here is plain system calls, so it can all be used before we are relocated.
+///
+/// Unsafe since the arguments can be pointers, the wrappers that only pass memory they borrow aren't.
+#[inline(always)]
+unsafe fn syscall(number: usize, arguments: [usize; 6]) -> Result<usize, Errno> {
+    let value: isize;
+    asm!(
+        "syscall",
+        inlateout("rax") number => value,
+        in("rdi") arguments[0],
+        in("rsi") arguments[1],
+        in("rdx") arguments[2],
+        in("r10") arguments[3],
+        in("r8") arguments[4],
+        in("r9") arguments[5],
+        out("rcx") _,
+        out("r11") _,
+        options(nostack)
+    );
+    result(value)
+}
+
+#[inline(always)]
+pub fn openat(directory: i32, path: &CStr, flags: i32, mode: u32) -> Result<i32, Errno> {
+    unsafe {
+        syscall(
+            OPENAT,
+            [
+                directory as usize,
+                path.as_ptr().addr(),
+                flags as usize,
+                mode as usize,
+                0,
+                0,
+            ],
+        )
+        .map(|fd| fd as i32)
+    }
+}
+
+#[inline(always)]
+pub fn read(fd: i32, buffer: &mut [u8]) -> Result<usize, Errno> {
+    unsafe {
+        syscall(
+            READ,
+            [
+                fd as usize,
+                buffer.as_mut_ptr().addr(),
+                buffer.len(),
+                0,
+                0,
+                0,
+            ],
+        )
+    }
+}
+
+#[inline(always)]
+pub fn pread64(fd: i32, buffer: &mut [u8], offset: u64) -> Result<usize, Errno> {
+    unsafe {
+        syscall(
+            PREAD64,
+            [
+                fd as usize,
+                buffer.as_mut_ptr().addr(),
+                buffer.len(),
+                offset as usize,
+                0,
+                0,
+            ],
+        )
+    }
+}
+
+/// One `write`, it can write less than all of `bytes`; see `io::write` for one that doesn't.
+#[inline(always)]
+pub fn write(fd: i32, bytes: &[u8]) -> Result<usize, Errno> {
+    unsafe {
+        syscall(
+            WRITE,
+            [fd as usize, bytes.as_ptr().addr(), bytes.len(), 0, 0, 0],
+        )
+    }
+}
+
+#[inline(always)]
+pub unsafe fn close(fd: i32) -> Result<(), Errno> {
+    syscall(CLOSE, [fd as usize, 0, 0, 0, 0, 0]).map(drop)
+}
+
+#[inline(always)]
+pub fn fstat(fd: i32) -> Result<Stat, Errno> {
+    let mut stat = Stat::default();
+    unsafe {
+        syscall(
+            FSTAT,
+            [fd as usize, (&mut stat as *mut Stat).addr(), 0, 0, 0, 0],
+        )?
+    };
+    Ok(stat)
+}
+
+/// `stat`, for `path` relative to `directory` (or `AT_FDCWD`).
+#[inline(always)]
+pub fn fstatat(directory: i32, path: &CStr, flags: i32) -> Result<Stat, Errno> {
+    let mut stat = Stat::default();
+    unsafe {
+        syscall(
+            NEWFSTATAT,
+            [
+                directory as usize,
+                path.as_ptr().addr(),
+                (&mut stat as *mut Stat).addr(),
+                flags as usize,
+                0,
+                0,
+            ],
+        )?
+    };
+    Ok(stat)
+}
+
+#[inline(always)]
+pub unsafe fn mmap(
+    pointer: *mut u8,
+    size: usize,
+    protection_flags: usize,
+    map_flags: usize,
+    file_descriptor: isize,
+    offset: usize,
+) -> Result<*mut u8, Errno> {
+    syscall(
+        MMAP,
+        [
+            pointer.addr(),
+            size,
+            protection_flags,
+            map_flags,
+            file_descriptor as usize,
+            offset,
+        ],
+    )
+    .map(|address| null_mut::<u8>().with_addr(address))
+}
+
+#[inline(always)]
+pub unsafe fn mprotect(
+    pointer: *mut u8,
+    size: usize,
+    protection_flags: usize,
+) -> Result<(), Errno> {
+    syscall(MPROTECT, [pointer.addr(), size, protection_flags, 0, 0, 0]).map(drop)
+}
+
+#[inline(always)]
+pub unsafe fn munmap(pointer: *mut u8, size: usize) -> Result<(), Errno> {
+    syscall(MUNMAP, [pointer.addr(), size, 0, 0, 0, 0]).map(drop)
+}
+
+#[inline(always)]
+pub unsafe fn ioctl(fd: i32, request: usize, argument: *mut ()) -> Result<usize, Errno> {
+    syscall(IOCTL, [fd as usize, request, argument.addr(), 0, 0, 0])
+}
+
+/// Reads the target of the symbolic link at `path` into `buffer`, returning its length; it isn't null terminated.
+#[inline(always)]
+pub fn readlink(path: &CStr, buffer: &mut [u8]) -> Result<usize, Errno> {
+    unsafe {
+        syscall(
+            READLINK,
+            [
+                path.as_ptr().addr(),
+                buffer.as_mut_ptr().addr(),
+                buffer.len(),
+                0,
+                0,
+                0,
+            ],
+        )
+    }
+}
+
+#[inline(always)]
+pub fn getpid() -> u32 {
+    // It can't fail.
+    unsafe { syscall(GETPID, [0; 6]) }.unwrap_or_default() as u32
+}
+
+#[inline(always)]
+pub fn getrandom(buffer: &mut [u8], flags: u32) -> Result<usize, Errno> {
+    unsafe {
+        syscall(
+            GETRANDOM,
+            [
+                buffer.as_mut_ptr().addr(),
+                buffer.len(),
+                flags as usize,
+                0,
+                0,
+                0,
+            ],
+        )
+    }
+}
+
+#[inline(always)]
+pub fn clock_gettime(clock: usize) -> Result<TimeSpec, Errno> {
+    let mut time_spec = TimeSpec::default();
+    unsafe {
+        syscall(
+            CLOCK_GETTIME,
+            [clock, (&mut time_spec as *mut TimeSpec).addr(), 0, 0, 0, 0],
+        )?
+    };
+    Ok(time_spec)
+}
+
+/// Sets the action for `signal` to `action` (unless it's `None`), returning the previous one.
+#[inline(always)]
+pub unsafe fn rt_sigaction(signal: i32, action: Option<&SigAction>) -> Result<SigAction, Errno> {
+    let mut previous = SigAction::default();
+    let action = action.map_or(null(), |action| action as *const SigAction);
+    syscall(
+        RT_SIGACTION,
+        [
+            signal as usize,
+            action.addr(),
+            (&mut previous as *mut SigAction).addr(),
+            // The size of the mask, the kernel only accepts its own:
+            size_of::<u64>(),
+            0,
+            0,
+        ],
+    )?;
+    Ok(previous)
+}
+
+#[inline(always)]
+pub unsafe fn prctl(option: i32, arguments: [usize; 4]) -> Result<usize, Errno> {
+    syscall(
+        PRCTL,
+        [
+            option as usize,
+            arguments[0],
+            arguments[1],
+            arguments[2],
+            arguments[3],
+            0,
+        ],
+    )
+}
+
+#[inline(always)]
+pub unsafe fn arch_prctl(code: usize, address: usize) -> Result<usize, Errno> {
+    syscall(ARCH_PRCTL, [code, address, 0, 0, 0, 0])
+}
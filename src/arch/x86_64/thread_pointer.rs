@@ -1,21 +1,14 @@
-use std::arch::asm;
+use core::arch::asm;
 
+use super::syscall;
 use crate::syscall_debug_assert;
 
 #[inline(always)]
 pub unsafe fn set_thread_pointer(new_pointer: *mut ()) {
-    const ARCH_PRCTL: usize = 158;
     const ARCH_SET_FS: usize = 4098;
 
-    asm!(
-        "syscall",
-        in("rax") ARCH_PRCTL,
-        in("rdi") ARCH_SET_FS,
-        in("rsi") new_pointer,
-        out("rcx") _,
-        out("r11") _,
-        options(nostack)
-    );
+    let result = syscall::arch_prctl(ARCH_SET_FS, new_pointer.addr());
+    syscall_debug_assert!(result.is_ok());
     syscall_debug_assert!(*new_pointer.cast::<*mut ()>() == new_pointer);
     syscall_debug_assert!(get_thread_pointer() == new_pointer);
 }
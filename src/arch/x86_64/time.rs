@@ -0,0 +1,54 @@
+use core::{
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use super::syscall::{self, Errno};
+use crate::{elf::symbol_version::RequiredVersion, shared_object::SharedObject, symbol_lookup};
+
+/// A clock that only goes forward, unaffected by changes to the system time.
+pub const CLOCK_MONOTONIC: usize = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct TimeSpec {
+    pub tv_sec: isize,
+    pub tv_nsec: isize,
+}
+
+/// The vDSO's `clock_gettime`, it returns `-errno` on failure.
+type VdsoClockGettime = unsafe extern "C" fn(clock: i32, time_spec: *mut TimeSpec) -> i32;
+
+/// `__vdso_clock_gettime`, null until `use_vdso` found it; until then every call is a system call.
+static VDSO_CLOCK_GETTIME: AtomicPtr<()> = AtomicPtr::new(null_mut());
+
+/// Makes `clock_gettime` call into the vDSO, which reads the time without entering the kernel.
+pub fn use_vdso(vdso: &SharedObject) {
+    let version = RequiredVersion {
+        name: "LINUX_2.6",
+        file: Some("linux-vdso.so.1"),
+    };
+    let Some((_, symbol)) =
+        (unsafe { symbol_lookup::find_in(vdso, "__vdso_clock_gettime", Some(&version)) })
+    else {
+        return;
+    };
+    let function = vdso.base.wrapping_byte_add(symbol.st_value);
+    VDSO_CLOCK_GETTIME.store(function.cast_mut(), Ordering::Relaxed);
+}
+
+/// NOTE: Called before we are relocated too, there is no vDSO to call into yet then.
+#[inline(always)]
+pub fn clock_gettime(clock: usize) -> Result<TimeSpec, Errno> {
+    let function = VDSO_CLOCK_GETTIME.load(Ordering::Relaxed);
+    if function.is_null() {
+        return syscall::clock_gettime(clock);
+    }
+
+    let function = unsafe { core::mem::transmute::<*mut (), VdsoClockGettime>(function) };
+    let mut time_spec = TimeSpec::default();
+    match unsafe { function(clock as i32, &mut time_spec) } {
+        0 => Ok(time_spec),
+        error => Err(Errno(-error)),
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{arch::io, cli::Options, events, linux::environment_variables::EnvironmentIter};
+
+/// Reports every auditing library named by `LD_AUDIT` or `--audit` (separated by colons) as ignored, see
+/// `rtld-audit(7)`.
+///
+/// Auditing isn't supported: the interface (`la_version`, `la_objopen`, ...) is code in the audit module, and we can't
+/// call into a library before we relocate libraries. Rather than map them and pretend, we say so, the way glibc reports
+/// one it can't load. Scripts that pass them still work, they just aren't audited.
+pub fn ignore_audit_modules(environment: EnvironmentIter, options: &Options) {
+    let environment_list = environment
+        .filter(|&(name, _)| name == "LD_AUDIT")
+        .map(|(_, value)| value);
+    for list in environment_list.chain(options.audit) {
+        for name in list.split(':').filter(|name| !name.is_empty()) {
+            events::error(
+                "cannot be loaded as audit interface: auditing is not supported",
+                name,
+            );
+            io::write(io::STD_ERR, "ERROR: miros: object '");
+            io::write(io::STD_ERR, name);
+            io::write(
+                io::STD_ERR,
+                "' cannot be loaded as audit interface: auditing is not supported; ignored.\n",
+            );
+        }
+    }
+}
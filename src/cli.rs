@@ -1,8 +1,487 @@
-use crate::{arch, io_macros::*};
+use alloc::{boxed::Box, ffi::CString};
+use core::{
+    ffi::{c_char, CStr},
+    ptr::null,
+    slice,
+};
+
+use crate::{
+    arch::{self, io},
+    elf::{
+        header::{ElfHeader, ET_EXEC},
+        program_header::ProgramHeader,
+    },
+    error::{self, LoaderError},
+    events,
+    fs::File,
+    graph,
+    io_macros::*,
+    linux::{
+        auxiliary_vector::{
+            self, AuxiliaryVectorItem, AuxiliaryVectorIter, AT_BASE, AT_ENTRY, AT_EXECFN, AT_NULL,
+            AT_PHDR, AT_PHNUM,
+        },
+        environment_variables::EnvironmentIter,
+    },
+    shared_object::SharedObject,
+    statistics,
+};
+
+/// The options glibc's `ld.so` accepts before the program when it's run directly, see `ld.so(8)`.
+///
+/// Each one stands in for (or adds to) the environment variable that drives the same thing.
+#[derive(Default)]
+pub(crate) struct Options {
+    /// `--library-path PATH`, used instead of `LD_LIBRARY_PATH`.
+    pub library_path: Option<&'static str>,
+    /// `--preload LIST`, loaded after `LD_PRELOAD`.
+    pub preload: Option<&'static str>,
+    /// `--argv0 STRING`, what the program sees as its name.
+    pub argv0: Option<&'static CStr>,
+    /// `--inhibit-cache`, don't use `/etc/ld.so.cache`.
+    pub inhibit_cache: bool,
+    /// `--inhibit-rpath LIST`, objects whose `DT_RPATH` and `DT_RUNPATH` are ignored.
+    pub inhibit_rpath: Option<&'static str>,
+    /// `--list`, list what the program would load instead of running it (like `LD_TRACE_LOADED_OBJECTS`).
+    pub list: bool,
+    /// `--verify-symbols`, also report symbols nothing defines (like `ldd -r`).
+    pub verify_symbols: bool,
+    /// `--unused`, report direct dependencies nothing binds to instead (like `ldd -u`).
+    pub unused: bool,
+    /// `--explain-symbol NAME[@VERSION]`, explain where references to a symbol bind instead of listing.
+    pub explain_symbol: Option<&'static str>,
+    /// `--check-collisions`, report symbols with more than one strong definition instead of listing.
+    pub check_collisions: bool,
+    /// `--graph=FORMAT`, print the dependency graph instead of the list.
+    pub graph: Option<graph::Format>,
+    /// `--stats`, report loader statistics at exit (like `LD_DEBUG=statistics`).
+    pub stats: bool,
+    /// `--audit LIST`, auditing libraries after `LD_AUDIT`'s; they are ignored, see `audit.rs`.
+    pub audit: Option<&'static str>,
+}
 
 pub(crate) fn run_cli() -> ! {
     syscall_println!(concat!(env!("CARGO_PKG_DESCRIPTION"), "\n"));
-    syscall_println!(bold!(underline!("Usage:"), " miros"), " <BINARY_PATH>\n");
-    syscall_println!("This doesn't work yet");
+    syscall_println!(
+        bold!(underline!("Usage:"), " miros"),
+        " [OPTIONS] <BINARY_PATH> [ARGUMENTS...]\n\n",
+        bold!(underline!("Options:")),
+        "\n  ",
+        bold!("--list"),
+        "                    list all dependencies and how they are resolved\n  ",
+        bold!("--verify-symbols"),
+        "          list, and report undefined symbols\n  ",
+        bold!("--unused"),
+        "                  report direct dependencies nothing binds to\n  ",
+        bold!("--explain-symbol"),
+        " NAME[@VERSION]\n                            explain where references to NAME bind, and why\n  ",
+        bold!("--check-collisions"),
+        "        report global symbols with more than one strong definition\n  ",
+        bold!("--graph"),
+        "=dot|json          print the dependency graph as Graphviz DOT or JSON\n  ",
+        bold!("--library-path"),
+        " PATH     use PATH instead of LD_LIBRARY_PATH\n  ",
+        bold!("--preload"),
+        " LIST          preload the objects named in LIST\n  ",
+        bold!("--argv0"),
+        " STRING          set argv[0] to STRING before running\n  ",
+        bold!("--inhibit-cache"),
+        "           don't use /etc/ld.so.cache\n  ",
+        bold!("--inhibit-rpath"),
+        " LIST      ignore DT_RPATH and DT_RUNPATH of the objects named in LIST\n  ",
+        bold!("--stats"),
+        "                   report loader statistics and timing at exit\n  ",
+        bold!("--audit"),
+        " LIST              accepted for compatibility, auditing is not supported\n  ",
+        bold!("--help"),
+        "                    display this help and exit\n  ",
+        bold!("--version"),
+        "                 output version information and exit\n",
+    );
+    arch::exit(0);
+}
+
+fn print_version() -> ! {
+    syscall_println!(bold!("miros ", env!("CARGO_PKG_VERSION")));
     arch::exit(0);
 }
+
+/// What the arguments we were started with ask us to do.
+pub(crate) enum Command {
+    /// Load the program, it comes right after the `option_count` arguments that were ours.
+    Run {
+        options: Options,
+        option_count: usize,
+    },
+    /// `--help`, or there was no program to run.
+    Help,
+    /// `--version`.
+    Version,
+}
+
+/// An option we can't make sense of, reported as `miros: {message}{option}`.
+pub(crate) struct OptionError {
+    message: &'static str,
+    option: &'static CStr,
+}
+
+impl OptionError {
+    fn exit(&self) -> ! {
+        io::write(io::STD_ERR, "miros: ");
+        io::write(io::STD_ERR, self.message);
+        io::write(io::STD_ERR, &self.option.to_string_lossy());
+        io::write(io::STD_ERR, "\nTry 'miros --help' for more information.\n");
+        arch::exit(1);
+    }
+}
+
+/// Parses the options between our name and the program's.
+unsafe fn parse_options(
+    argument_pointer: *const *const c_char,
+    argument_count: usize,
+) -> Result<Command, OptionError> {
+    let mut options = Options::default();
+    let error = |message, option| Err(OptionError { message, option });
+
+    let mut index = 1;
+    while index < argument_count {
+        let option = argument(argument_pointer, index);
+        match option.to_bytes() {
+            b"--help" => return Ok(Command::Help),
+            b"--version" => return Ok(Command::Version),
+            b"--list" => options.list = true,
+            b"--verify-symbols" => (options.list, options.verify_symbols) = (true, true),
+            b"--unused" => (options.list, options.unused) = (true, true),
+            b"--check-collisions" => (options.list, options.check_collisions) = (true, true),
+            b"--inhibit-cache" => options.inhibit_cache = true,
+            b"--stats" => options.stats = true,
+            // Everything after `--` belongs to the program:
+            b"--" if index + 1 < argument_count => {
+                return Ok(Command::Run {
+                    options,
+                    option_count: index,
+                })
+            }
+            b"--" => break,
+            b"--library-path" | b"--preload" | b"--argv0" | b"--inhibit-rpath"
+            | b"--explain-symbol" | b"--audit" => {
+                index += 1;
+                if index >= argument_count {
+                    return error("option requires an argument: ", option);
+                }
+                let value = argument(argument_pointer, index);
+                // NOTE: Like the environment, we take arguments as they are, without checking they're UTF-8.
+                let value_str = core::str::from_utf8_unchecked(value.to_bytes());
+                match option.to_bytes() {
+                    b"--library-path" => options.library_path = Some(value_str),
+                    b"--preload" => options.preload = Some(value_str),
+                    b"--argv0" => options.argv0 = Some(value),
+                    b"--audit" => options.audit = Some(value_str),
+                    b"--explain-symbol" => {
+                        (options.list, options.explain_symbol) = (true, Some(value_str))
+                    }
+                    _ => options.inhibit_rpath = Some(value_str),
+                }
+            }
+            bytes if bytes.starts_with(b"--graph=") => {
+                let Some(format) = graph::Format::from_name(&bytes[b"--graph=".len()..]) else {
+                    return error("unknown graph format (expected dot or json): ", option);
+                };
+                (options.list, options.graph) = (true, Some(format));
+            }
+            bytes if bytes.starts_with(b"--") => return error("unrecognized option: ", option),
+            // The first argument that isn't an option is the program:
+            _ => {
+                return Ok(Command::Run {
+                    options,
+                    option_count: index - 1,
+                })
+            }
+        }
+        index += 1;
+    }
+
+    // We ran out of arguments before finding a program:
+    Ok(Command::Help)
+}
+
+/// The `index`th argument, which lives as long as the program does.
+unsafe fn argument(argument_pointer: *const *const c_char, index: usize) -> &'static CStr {
+    CStr::from_ptr(*argument_pointer.add(index))
+}
+
+/// Loads the program we were asked to run directly (`miros [OPTIONS] ./program args...`) in place of the kernel.
+///
+/// Afterwards the stack looks exactly like the kernel had started the program with us as its interpreter:
+/// our own `argv[0]` and options are gone and the auxiliary vector describes the program instead of us.
+/// Returns the executable, the name we were started under, and the options we were given.
+pub(crate) unsafe fn load_executable(
+    stack_pointer: *mut usize,
+    miros_base: *const (),
+) -> (SharedObject, &'static CStr, Options) {
+    let argument_count = *stack_pointer;
+    let argument_pointer = stack_pointer.add(1) as *const *const c_char;
+    let (options, option_count) = match parse_options(argument_pointer, argument_count) {
+        Ok(Command::Run {
+            options,
+            option_count,
+        }) => (options, option_count),
+        Ok(Command::Help) => run_cli(),
+        Ok(Command::Version) => print_version(),
+        Err(error) => error.exit(),
+    };
+
+    let miros_name = argument(argument_pointer, 0);
+    let program_name = argument(argument_pointer, 1 + option_count);
+
+    // The path is opened as is, it doesn't have to be UTF-8; only what we report about it has the invalid parts replaced.
+    // NOTE: Leaked, errors can be reported as long as the program runs.
+    let path: &'static str =
+        Box::leak(program_name.to_string_lossy().into_owned().into_boxed_str());
+    error::set_program_name(path);
+    let file = match File::open_c_str(program_name) {
+        Ok(file) => file,
+        Err(errno) => {
+            let error = LoaderError::CannotOpen { name: path, errno };
+            events::error(&error.message(), path);
+            error.exit();
+        }
+    };
+    statistics::count_file_opened();
+
+    // Non-PIE executables can't be moved, so if they were linked where we ended up, there is nothing we can do:
+    let (header, program_header_table) = SharedObject::read_headers(&file, path);
+    if header.e_type == ET_EXEC {
+        let (start, end) = SharedObject::bounds_of(null(), &program_header_table);
+        let (miros_start, miros_end) = miros_bounds(miros_base);
+        if start < miros_end && miros_start < end {
+            LoaderError::AddressConflict {
+                path,
+                start,
+                end,
+                miros: Some((miros_start, miros_end)),
+            }
+            .exit();
+        }
+    }
+
+    // NOTE: `CString` is needed so the name is null terminated; it lives as long as the program does.
+    let name: &'static CStr = Box::leak(CString::from(program_name).into_boxed_c_str());
+    let executable = SharedObject::from_file(file, name);
+
+    skip_arguments(stack_pointer, 1 + option_count);
+    if let Some(argv0) = options.argv0 {
+        *stack_pointer.add(1) = argv0.as_ptr().addr();
+    }
+
+    // Describe the program, not us:
+    let auxiliary_vector = AuxiliaryVectorIter::from_environment_iter(
+        EnvironmentIter::from_stack_pointer(stack_pointer),
+    )
+    .into_inner() as *mut AuxiliaryVectorItem;
+    auxiliary_vector::overwrite(
+        auxiliary_vector,
+        AT_PHDR,
+        executable.program_header_table.as_ptr().addr(),
+    );
+    auxiliary_vector::overwrite(
+        auxiliary_vector,
+        AT_PHNUM,
+        executable.program_header_table.len(),
+    );
+    auxiliary_vector::overwrite(
+        auxiliary_vector,
+        AT_ENTRY,
+        executable.base.addr() + header.e_entry,
+    );
+    auxiliary_vector::overwrite(auxiliary_vector, AT_BASE, miros_base.addr());
+    auxiliary_vector::overwrite(auxiliary_vector, AT_EXECFN, program_name.as_ptr().addr());
+
+    (executable, miros_name, options)
+}
+
+/// The page aligned `(start, end)` addresses we are mapped at.
+unsafe fn miros_bounds(miros_base: *const ()) -> (usize, usize) {
+    let header = &*(miros_base as *const ElfHeader);
+    let program_header_table = slice::from_raw_parts(
+        miros_base.byte_add(header.e_phoff) as *const ProgramHeader,
+        header.e_phnum as usize,
+    );
+    SharedObject::bounds_of(miros_base, program_header_table)
+}
+
+/// Removes the first `count` arguments from the stack, so the program sees its own name as `argv[0]`.
+///
+/// Everything after the arguments (the environment and auxiliary vector) is shifted down to close the gap:
+///
+/// ```no_run
+/// |---------------------|     |---------------------|
+/// | arg_count           |     | arg_count - count   |
+/// |---------------------|     |---------------------|
+/// | arg_values...       |  -> | arg_values[count..] |
+/// |---------------------|     |---------------------|
+/// | null                |     | null                |
+/// |---------------------|     |---------------------|
+/// | env_pointers...     |     | env_pointers...     |
+/// |---------------------|     |---------------------|
+/// | null                |     | null                |
+/// |---------------------|     |---------------------|
+/// | auxiliary_vector... |     | auxiliary_vector... |
+/// |---------------------|     |---------------------|
+/// ```
+pub(crate) unsafe fn skip_arguments(stack_pointer: *mut usize, count: usize) {
+    *stack_pointer -= count;
+    let (mut read, mut write) = (stack_pointer.add(1 + count), stack_pointer.add(1));
+
+    // The arguments and the environment, each null terminated:
+    for _ in 0..2 {
+        loop {
+            let value = *read;
+            *write = value;
+            (read, write) = (read.add(1), write.add(1));
+            if value == 0 {
+                break;
+            }
+        }
+    }
+
+    // The auxiliary vector, up to and including `AT_NULL`:
+    loop {
+        let (a_type, a_val) = (*read, *read.add(1));
+        (*write, *write.add(1)) = (a_type, a_val);
+        (read, write) = (read.add(2), write.add(2));
+        if a_type == AT_NULL {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Parses `arguments` like they were on our stack, `arguments[0]` being our own name.
+    fn parse(arguments: &[&str]) -> Result<Command, OptionError> {
+        // NOTE: Leaked, the parsed options borrow them for `'static` like they would the real stack.
+        let pointers: Vec<*const c_char> = arguments
+            .iter()
+            .map(|argument| CString::new(*argument).unwrap().into_raw() as *const c_char)
+            .collect();
+        unsafe { parse_options(pointers.as_ptr(), pointers.len()) }
+    }
+
+    fn run(arguments: &[&str]) -> (Options, usize) {
+        match parse(arguments) {
+            Ok(Command::Run {
+                options,
+                option_count,
+            }) => (options, option_count),
+            _ => panic!("{arguments:?} should run a program"),
+        }
+    }
+
+    fn error(arguments: &[&str]) -> (&'static str, &'static str) {
+        match parse(arguments) {
+            Err(error) => (error.message, error.option.to_str().unwrap()),
+            _ => panic!("{arguments:?} should be an error"),
+        }
+    }
+
+    #[test]
+    fn options_come_before_the_program() {
+        let (options, option_count) = run(&[
+            "miros",
+            "--list",
+            "--library-path",
+            "/opt/lib",
+            "--inhibit-cache",
+            "./program",
+            "--preload",
+            "argument",
+        ]);
+        assert_eq!(option_count, 4);
+        assert!(options.list && options.inhibit_cache);
+        assert_eq!(options.library_path, Some("/opt/lib"));
+        // Everything after the program is its own:
+        assert_eq!(options.preload, None);
+    }
+
+    #[test]
+    fn listing_modes_imply_list() {
+        let (options, _) = run(&["miros", "--unused", "./program"]);
+        assert!(options.list && options.unused);
+        let (options, _) = run(&[
+            "miros",
+            "--explain-symbol",
+            "malloc@GLIBC_2.2.5",
+            "./program",
+        ]);
+        assert!(options.list);
+        assert_eq!(options.explain_symbol, Some("malloc@GLIBC_2.2.5"));
+        let (options, _) = run(&["miros", "--graph=json", "./program"]);
+        assert!(options.list && matches!(options.graph, Some(graph::Format::Json)));
+    }
+
+    #[test]
+    fn double_dash_ends_the_options() {
+        let (options, option_count) = run(&["miros", "--stats", "--", "--list"]);
+        // The program is whatever comes after `--`, even if it looks like an option:
+        assert_eq!(option_count, 2);
+        assert!(options.stats && !options.list);
+    }
+
+    #[test]
+    fn no_program_shows_the_help() {
+        assert!(matches!(parse(&["miros"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["miros", "--list"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["miros", "--"]), Ok(Command::Help)));
+        assert!(matches!(
+            parse(&["miros", "--help", "./program"]),
+            Ok(Command::Help)
+        ));
+        assert!(matches!(
+            parse(&["miros", "--version"]),
+            Ok(Command::Version)
+        ));
+    }
+
+    #[test]
+    fn missing_option_arguments() {
+        assert_eq!(
+            error(&["miros", "--library-path"]),
+            ("option requires an argument: ", "--library-path")
+        );
+        assert_eq!(
+            error(&["miros", "--list", "--argv0"]),
+            ("option requires an argument: ", "--argv0")
+        );
+    }
+
+    #[test]
+    fn bad_options() {
+        assert_eq!(
+            error(&["miros", "--frobnicate", "./program"]),
+            ("unrecognized option: ", "--frobnicate")
+        );
+        assert_eq!(
+            error(&["miros", "--graph=svg", "./program"]),
+            (
+                "unknown graph format (expected dot or json): ",
+                "--graph=svg"
+            )
+        );
+    }
+
+    #[test]
+    fn audit_is_accepted() {
+        // glibc's scripts pass it, it's only reported as ignored once we start loading:
+        let (options, option_count) = run(&["miros", "--audit", "libaudit.so", "./program"]);
+        assert_eq!(option_count, 2);
+        assert_eq!(options.audit, Some("libaudit.so"));
+        assert_eq!(
+            error(&["miros", "--audit"]),
+            ("option requires an argument: ", "--audit")
+        );
+    }
+}
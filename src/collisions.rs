@@ -0,0 +1,262 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    arch::{io, relocation::R_X86_64_COPY},
+    elf::{
+        symbol::{STB_GLOBAL, STT_FUNC, STT_OBJECT},
+        symbol_version::RequiredVersion,
+    },
+    loaded_objects,
+    loader_lock::LOADER_LOCK,
+    shared_object::SharedObject,
+    symbol_lookup::{self, Definition},
+};
+
+/// A definition of a symbol in one object.
+#[derive(Clone, Copy)]
+struct Instance {
+    shared_object: &'static SharedObject,
+    symbol_index: usize,
+    /// `None` if it's unversioned.
+    version: Option<&'static str>,
+}
+
+/// Reports every global function or object with more than one strong definition (`--check-collisions`):
+///
+/// ```text
+/// `compress2' (FUNC) has 2 strong definitions:
+///     /usr/lib/libfoo.so  wins
+///     /lib/x86_64-linux-gnu/libz.so.1 (ZLIB_1.2.0)  loses
+///       still used by: /lib/x86_64-linux-gnu/libz.so.1 (no relocation for it, so any uses inside it were bound when it was linked)
+/// ```
+///
+/// Whichever copy comes first in the scope interposes on the others, but objects that never ask the dynamic linker
+/// (because their uses were bound at link time, or the symbol is protected) keep using their own copy.
+/// Returns whether there were any collisions.
+///
+/// NOTE: We leave ourselves out, we stand in for glibc's dynamic linker and some of what we define is meant to overlap
+/// with `libc.so.6`.
+pub unsafe fn check_collisions(interpreter: &SharedObject) -> bool {
+    let guard = LOADER_LOCK.lock();
+    let mut objects = Vec::new();
+    while let Some(shared_object) = loaded_objects::get(&guard, objects.len()) {
+        objects.push(shared_object);
+    }
+    drop(guard);
+    objects.retain(|&shared_object| !core::ptr::eq(shared_object, interpreter));
+
+    let mut definitions = Vec::new();
+    for &shared_object in &objects {
+        let Some(hash_table) = shared_object.hash_table.as_ref() else {
+            continue;
+        };
+        // The executable's copies of library data aren't definitions of their own:
+        let copy_relocated: Vec<usize> = shared_object
+            .relocations
+            .iter()
+            .filter(|rela| rela.r_type() == R_X86_64_COPY)
+            .map(|rela| rela.r_sym() as usize)
+            .collect();
+        for symbol_index in 1..hash_table.symbol_count() {
+            let symbol = shared_object.symbol_table.get(symbol_index);
+            if copy_relocated.contains(&symbol_index)
+                || !symbol.is_exported_definition()
+                || symbol.st_bind() != STB_GLOBAL
+                || !matches!(symbol.st_type(), STT_FUNC | STT_OBJECT)
+            {
+                continue;
+            }
+            let version = shared_object
+                .symbol_versions
+                .defined(symbol_index, &shared_object.string_table)
+                .and_then(|defined| defined.name);
+            let instance = Instance {
+                shared_object,
+                symbol_index,
+                version,
+            };
+            let object = shared_object as *const SharedObject;
+            definitions.push((shared_object.symbol_name(symbol_index), object, instance));
+        }
+    }
+
+    let collisions = collisions(&definitions);
+    for (name, copies) in &collisions {
+        report(name, copies, &objects);
+    }
+    !collisions.is_empty()
+}
+
+/// Groups `definitions` (a name, the object defining it, and the definition) by name, in the order each name was first
+/// defined, and keeps the names that more than one object defines.
+///
+/// Versions don't keep definitions apart: an unversioned definition satisfies a versioned reference, so a library that
+/// bundles its own `compress2` interposes on `compress2@@ZLIB_1.2.0` just the same. Different versions within one object
+/// (`memcpy@GLIBC_2.2.5` and `memcpy@@GLIBC_2.14`) are how it's meant to work though, not a collision.
+fn collisions<Object: PartialEq, T: Copy>(
+    definitions: &[(&'static str, Object, T)],
+) -> Vec<(&'static str, Vec<T>)> {
+    let mut by_name: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut order = Vec::new();
+    for (index, (name, ..)) in definitions.iter().enumerate() {
+        let indices = by_name.entry(name).or_default();
+        if indices.is_empty() {
+            order.push(*name);
+        }
+        indices.push(index);
+    }
+
+    let mut collisions = Vec::new();
+    for name in order {
+        let indices = &by_name[name];
+        let first_object = &definitions[indices[0]].1;
+        if indices
+            .iter()
+            .all(|&index| definitions[index].1 == *first_object)
+        {
+            continue;
+        }
+        let copies = indices.iter().map(|&index| definitions[index].2).collect();
+        collisions.push((name, copies));
+    }
+    collisions
+}
+
+unsafe fn report(name: &'static str, copies: &[Instance], objects: &[&'static SharedObject]) {
+    let first = copies[0];
+    let symbol = first.shared_object.symbol_table.get(first.symbol_index);
+
+    io::write(io::STD_OUT, "`");
+    io::write(io::STD_OUT, name);
+    io::write(io::STD_OUT, "' (");
+    io::write(io::STD_OUT, symbol.type_name());
+    io::write(
+        io::STD_OUT,
+        &format!(") has {} strong definitions:\n", copies.len()),
+    );
+
+    // What a reference without anything special going on would bind to, for each version the copies have:
+    let mut winners = Vec::new();
+    for copy in copies {
+        let required = copy.version.map(|version| RequiredVersion {
+            name: version,
+            file: None,
+        });
+        if let Some(winner) = symbol_lookup::lookup(name, required.as_ref(), None) {
+            winners.push(winner);
+        }
+    }
+
+    for copy in copies {
+        let wins = winners.iter().any(|winner: &Definition| {
+            core::ptr::eq(winner.shared_object, copy.shared_object)
+                && winner.symbol_index == copy.symbol_index
+        });
+        io::write(io::STD_OUT, "    ");
+        io::write(io::STD_OUT, copy.shared_object.display_name());
+        if let Some(version) = copy.version {
+            io::write(io::STD_OUT, " (");
+            io::write(io::STD_OUT, version);
+            io::write(io::STD_OUT, ")");
+        }
+        io::write(io::STD_OUT, if wins { "  wins\n" } else { "  loses\n" });
+        if !wins {
+            report_users(name, copy, objects);
+        }
+    }
+    io::write(io::STD_OUT, "\n");
+}
+
+/// Reports the objects that end up using a losing copy anyway.
+unsafe fn report_users(name: &str, copy: &Instance, objects: &[&'static SharedObject]) {
+    let mut users = Vec::new();
+    let mut references_itself = false;
+    for &shared_object in objects {
+        for rela in shared_object.relocations.iter() {
+            let symbol_index = rela.r_sym() as usize;
+            if symbol_index == 0 || shared_object.symbol_name(symbol_index) != name {
+                continue;
+            }
+            if core::ptr::eq(shared_object, copy.shared_object) {
+                references_itself = true;
+            }
+            let Some(definition) =
+                symbol_lookup::resolve(shared_object, rela).and_then(|binding| binding.definition)
+            else {
+                continue;
+            };
+            if core::ptr::eq(definition.shared_object, copy.shared_object)
+                && definition.symbol_index == copy.symbol_index
+                && !users.contains(&shared_object.display_name())
+            {
+                users.push(shared_object.display_name());
+            }
+        }
+    }
+
+    for user in users {
+        io::write(io::STD_OUT, "      still used by: ");
+        io::write(io::STD_OUT, user);
+        io::write(io::STD_OUT, "\n");
+    }
+    if !references_itself {
+        io::write(io::STD_OUT, "      still used by: ");
+        io::write(io::STD_OUT, copy.shared_object.display_name());
+        io::write(
+            io::STD_OUT,
+            " (no relocation for it, so any uses inside it were bound when it was linked)\n",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_dont_keep_copies_apart() {
+        // A library bundling zlib without versions, loaded before the real one:
+        let definitions = [
+            ("compress2", "libfoo.so", ("libfoo.so", None)),
+            ("deflate", "libfoo.so", ("libfoo.so", None)),
+            ("compress2", "libz.so.1", ("libz.so.1", Some("ZLIB_1.2.0"))),
+            ("inflate", "libz.so.1", ("libz.so.1", Some("ZLIB_1.2.0"))),
+            ("deflate", "libz.so.1", ("libz.so.1", Some("ZLIB_1.2.0"))),
+        ];
+        assert_eq!(
+            collisions(&definitions),
+            [
+                (
+                    "compress2",
+                    vec![("libfoo.so", None), ("libz.so.1", Some("ZLIB_1.2.0"))]
+                ),
+                (
+                    "deflate",
+                    vec![("libfoo.so", None), ("libz.so.1", Some("ZLIB_1.2.0"))]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn versions_within_one_object_dont_collide() {
+        let definitions = [
+            ("memcpy", "libc.so.6", Some("GLIBC_2.2.5")),
+            ("memcpy", "libc.so.6", Some("GLIBC_2.14")),
+        ];
+        assert!(collisions(&definitions).is_empty());
+
+        let definitions = [
+            ("memcpy", "libc.so.6", Some("GLIBC_2.2.5")),
+            ("memcpy", "libc.so.6", Some("GLIBC_2.14")),
+            ("memcpy", "libfast.so", None),
+        ];
+        assert_eq!(
+            collisions(&definitions),
+            [(
+                "memcpy",
+                vec![Some("GLIBC_2.2.5"), Some("GLIBC_2.14"), None]
+            )]
+        );
+    }
+}
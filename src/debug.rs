@@ -0,0 +1,154 @@
+use core::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+
+use crate::{
+    arch::{
+        exit, io,
+        syscall::{self, O_APPEND, O_CREAT, O_NOFOLLOW, O_WRONLY},
+    },
+    fs::{self, File},
+    linux::environment_variables::EnvironmentIter,
+};
+
+// `LD_DEBUG` categories:
+pub const DEBUG_LIBS: u32 = 1 << 0;
+pub const DEBUG_RELOC: u32 = 1 << 1;
+pub const DEBUG_FILES: u32 = 1 << 2;
+pub const DEBUG_SYMBOLS: u32 = 1 << 3;
+pub const DEBUG_BINDINGS: u32 = 1 << 4;
+pub const DEBUG_VERSIONS: u32 = 1 << 5;
+pub const DEBUG_STATISTICS: u32 = 1 << 6;
+pub const DEBUG_UNUSED: u32 = 1 << 7;
+
+/// The categories glibc accepts, what they turn on, and their description in `LD_DEBUG=help`.
+const CATEGORIES: [(&str, u32, &str); 9] = [
+    ("libs", DEBUG_LIBS, "display library search paths"),
+    ("reloc", DEBUG_RELOC, "display relocation processing"),
+    ("files", DEBUG_FILES, "display progress for input file"),
+    ("symbols", DEBUG_SYMBOLS, "display symbol table processing"),
+    (
+        "bindings",
+        DEBUG_BINDINGS,
+        "display information about symbol binding",
+    ),
+    ("versions", DEBUG_VERSIONS, "display version dependencies"),
+    (
+        "all",
+        DEBUG_LIBS | DEBUG_RELOC | DEBUG_FILES | DEBUG_SYMBOLS | DEBUG_BINDINGS | DEBUG_VERSIONS,
+        "all previous options combined",
+    ),
+    (
+        "statistics",
+        DEBUG_STATISTICS,
+        "display relocation statistics",
+    ),
+    ("unused", DEBUG_UNUSED, "determined unused DSOs"),
+];
+
+/// Which categories are turned on.
+static DEBUG_MASK: AtomicU32 = AtomicU32::new(0);
+/// Where the output goes, standard error unless `LD_DEBUG_OUTPUT` says otherwise.
+static DEBUG_FILE_DESCRIPTOR: AtomicI32 = AtomicI32::new(io::STD_ERR);
+
+/// Only privileged programs on systems that opted in (by creating this file) can be debugged.
+const SUID_DEBUG_FILE: &str = "/etc/suid-debug";
+
+/// Reads `LD_DEBUG` (categories separated by commas, colons or spaces) and `LD_DEBUG_OUTPUT`.
+///
+/// `LD_DEBUG=help` lists the categories and exits.
+pub fn initialize(environment: EnvironmentIter, secure: bool) {
+    if secure && fs::metadata(SUID_DEBUG_FILE).is_err() {
+        return;
+    }
+
+    let (mut mask, mut output) = (0, None);
+    for (name, value) in environment {
+        match name {
+            "LD_DEBUG" => {
+                for category in value
+                    .split([',', ':', ' '])
+                    .filter(|category| !category.is_empty())
+                {
+                    if category == "help" {
+                        print_help();
+                    }
+                    match CATEGORIES.iter().find(|(name, _, _)| *name == category) {
+                        Some((_, bits, _)) => mask |= bits,
+                        None => {
+                            io::write(io::STD_ERR, "warning: debug option `");
+                            io::write(io::STD_ERR, category);
+                            io::write(io::STD_ERR, "' unknown; try LD_DEBUG=help\n");
+                        }
+                    }
+                }
+            }
+            // NOTE: In secure mode someone could use this to create or append to files they can't otherwise touch.
+            "LD_DEBUG_OUTPUT" if !secure => output = Some(value),
+            _ => (),
+        }
+    }
+
+    // Each process gets a file of its own, so the output of a program and its children doesn't interleave:
+    if let Some(path) = output.filter(|_| mask != 0) {
+        let file = File::open_with(
+            &format!("{path}.{}", syscall::getpid()),
+            O_WRONLY | O_APPEND | O_CREAT | O_NOFOLLOW,
+            0o666,
+        );
+        if let Ok(file) = file {
+            // NOTE: Never closed, the output is needed until the program exits.
+            DEBUG_FILE_DESCRIPTOR.store(file.into_raw_fd(), Ordering::Relaxed);
+        }
+    }
+    DEBUG_MASK.store(mask, Ordering::Relaxed);
+}
+
+/// Turns `categories` on, for options that do the same as an `LD_DEBUG` category.
+pub fn enable(categories: u32) {
+    DEBUG_MASK.fetch_or(categories, Ordering::Relaxed);
+}
+
+/// Whether any of the `categories` are turned on.
+pub fn is_enabled(categories: u32) -> bool {
+    DEBUG_MASK.load(Ordering::Relaxed) & categories != 0
+}
+
+/// Writes one line of debug output, prefixed by our pid like glibc does.
+pub fn write_line(parts: &[&str]) {
+    // The whole line goes out in one write, so lines from different processes sharing the output don't mix:
+    let mut line = format!("{:>5}:\t", syscall::getpid());
+    for part in parts {
+        line.push_str(part);
+    }
+    line.push('\n');
+    io::write(DEBUG_FILE_DESCRIPTOR.load(Ordering::Relaxed), &line);
+}
+
+fn print_help() -> ! {
+    io::write(
+        io::STD_OUT,
+        "Valid options for the LD_DEBUG environment variable are:\n\n",
+    );
+    for (name, _, description) in CATEGORIES {
+        io::write(io::STD_OUT, &format!("  {name:<10}  {description}\n"));
+    }
+    io::write(
+        io::STD_OUT,
+        concat!(
+            "  help        display this help message and exit\n\n",
+            "To direct the debugging output into a file instead of standard output\n",
+            "a filename can be specified using the LD_DEBUG_OUTPUT environment variable.\n",
+        ),
+    );
+    exit::exit(0);
+}
+
+/// Writes a line of debug output if `$category` is turned on; the parts are `&str`s and only evaluated if it is.
+macro_rules! debug_println {
+    ($category:expr, $($part:expr),+ $(,)?) => {
+        if $crate::debug::is_enabled($category) {
+            $crate::debug::write_line(&[$($part),+]);
+        }
+    };
+}
+
+pub(crate) use debug_println;
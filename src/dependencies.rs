@@ -0,0 +1,213 @@
+use alloc::{boxed::Box, ffi::CString, string::String, vec::Vec};
+
+use crate::{
+    arch::syscall::Errno,
+    debug::{self, debug_println, DEBUG_FILES, DEBUG_VERSIONS},
+    elf::header::ElfHeader,
+    error::LoaderError,
+    events,
+    fs::File,
+    library_search::LibrarySearch,
+    loaded_objects,
+    loader_lock::LOADER_LOCK,
+    shared_object::SharedObject,
+};
+
+/// The name glibc's dynamic linker goes by; `libc.so.6` asks for it, but we are standing in for it.
+#[cfg(target_arch = "x86_64")]
+pub const INTERPRETER_SONAME: &str = "ld-linux-x86-64.so.2";
+
+/// Finds an already loaded object that goes by `name`, whether that's its soname, its path, or the file name it was found under.
+pub fn find_loaded(name: &str) -> Option<&'static SharedObject> {
+    let guard = LOADER_LOCK.lock();
+
+    let mut index = 0;
+    while let Some(shared_object) = loaded_objects::get(&guard, index) {
+        if goes_by(shared_object, name) {
+            return Some(shared_object);
+        }
+        index += 1;
+    }
+    None
+}
+
+/// Whether `shared_object` can be referred to as `name`.
+pub fn goes_by(shared_object: &SharedObject, name: &str) -> bool {
+    let path = shared_object.path();
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    shared_object.soname() == Some(name) || path == name || file_name == name
+}
+
+/// Whether `name` refers to us (the dynamic linker) rather than something that needs loading.
+pub fn is_interpreter(name: &str) -> bool {
+    name == INTERPRETER_SONAME
+}
+
+/// Searches for, maps, and registers the library `name` on behalf of `requester`.
+///
+/// Returns why if the library couldn't be found; it's up to the caller to decide if that's fatal.
+pub unsafe fn load_library(
+    name: &str,
+    requester: &SharedObject,
+    executable: &SharedObject,
+    library_search: &LibrarySearch,
+) -> Result<&'static SharedObject, Errno> {
+    let (file, path) = library_search.find(name, requester, executable)?;
+    Ok(map_library(file, path))
+}
+
+/// Maps and registers a library that has already been found.
+pub unsafe fn map_library(file: File, path: String) -> &'static SharedObject {
+    // NOTE: Paths can't contain null bytes, the kernel wouldn't have opened them.
+    let name: &'static _ = Box::leak(CString::new(path).unwrap_unchecked().into_boxed_c_str());
+    let shared_object = loaded_objects::add(SharedObject::from_file(file, name));
+
+    if debug::is_enabled(DEBUG_FILES) {
+        let (start, end) = shared_object.mapped_bounds();
+        // The first segment maps the start of the file, ELF header included:
+        let header = &*(start as *const ElfHeader);
+        debug::write_line(&["file=", shared_object.path(), " [0];  generating link map"]);
+        debug::write_line(&[&format!(
+            "  dynamic: {:#018x}  base: {:#018x}   size: {:#018x}",
+            (*shared_object.link_map.get()).l_ld.addr(),
+            shared_object.base.addr(),
+            end - start,
+        )]);
+        debug::write_line(&[&format!(
+            "    entry: {:#018x}  phdr: {:#018x}  phnum: {:>18}",
+            shared_object.base.addr() + header.e_entry,
+            shared_object.program_header_table.as_ptr().addr(),
+            shared_object.program_header_table.len(),
+        )]);
+        debug::write_line(&[""]);
+    }
+    shared_object
+}
+
+/// A `DT_NEEDED` entry, and what satisfied it.
+pub struct Dependency {
+    pub requester: &'static SharedObject,
+    pub name: &'static str,
+    pub target: Target,
+}
+
+pub enum Target {
+    /// Loaded because of this entry.
+    Loaded(&'static SharedObject),
+    /// An object that was already loaded (by an earlier entry, or as a preload).
+    AlreadyLoaded(&'static SharedObject),
+    /// Us, we are always loaded.
+    Interpreter,
+    /// It couldn't be found, only possible when tracing.
+    NotFound,
+}
+
+/// Loads every `DT_NEEDED` library of every loaded object, breadth first, so they end up in the same order glibc would put them.
+///
+/// Returns every entry, in the order they were processed, with what satisfied it.
+/// When `tracing` (listing what would be loaded), a missing library isn't fatal, it's just recorded as `Target::NotFound`.
+pub unsafe fn load_needed_libraries(
+    executable: &SharedObject,
+    library_search: &LibrarySearch,
+    tracing: bool,
+) -> Vec<Dependency> {
+    let guard = LOADER_LOCK.lock();
+    let mut dependencies = Vec::new();
+
+    // NOTE: Each object we load is appended to the list, so this loop picks up their dependencies too.
+    let mut index = 0;
+    while let Some(shared_object) = loaded_objects::get(&guard, index) {
+        for name in shared_object.needed_library_names() {
+            let target = if is_interpreter(name) {
+                Some(Target::Interpreter)
+            } else if let Some(loaded) = find_loaded(name) {
+                Some(Target::AlreadyLoaded(loaded))
+            } else if dependencies.iter().any(|dependency: &Dependency| {
+                dependency.name == name && matches!(dependency.target, Target::NotFound)
+            }) {
+                Some(Target::NotFound)
+            } else {
+                None
+            };
+            if let Some(target) = target {
+                dependencies.push(Dependency {
+                    requester: shared_object,
+                    name,
+                    target,
+                });
+                continue;
+            }
+            debug_println!(
+                DEBUG_FILES,
+                "file=",
+                name,
+                " [0];  needed by ",
+                shared_object.display_name(),
+                " [0]"
+            );
+
+            let target = match load_library(name, shared_object, executable, library_search) {
+                Ok(loaded) => Target::Loaded(loaded),
+                Err(errno) => {
+                    events::error("cannot open shared object file", name);
+                    if !tracing {
+                        LoaderError::CannotOpen { name, errno }.exit();
+                    }
+                    Target::NotFound
+                }
+            };
+            dependencies.push(Dependency {
+                requester: shared_object,
+                name,
+                target,
+            });
+        }
+        index += 1;
+    }
+    dependencies
+}
+
+/// Reports every version each loaded object needs from its libraries (`LD_DEBUG=versions`).
+///
+/// NOTE: Like glibc, a version that isn't defined is also reported as the lookup for it will fail.
+pub unsafe fn debug_version_requirements() {
+    if !debug::is_enabled(DEBUG_VERSIONS) {
+        return;
+    }
+    let guard = LOADER_LOCK.lock();
+
+    let mut index = 0;
+    while let Some(shared_object) = loaded_objects::get(&guard, index) {
+        index += 1;
+        let versions = &shared_object.symbol_versions;
+        for required in versions.requirements(&shared_object.string_table) {
+            let Some(file) = required.file else {
+                continue;
+            };
+            let Some(library) = find_loaded(file) else {
+                continue;
+            };
+            debug::write_line(&[
+                "checking for version `",
+                required.name,
+                "' in file ",
+                library.display_name(),
+                " [0] required by file ",
+                shared_object.display_name(),
+                " [0]",
+            ]);
+            if !library
+                .symbol_versions
+                .defines(required.name, &library.string_table)
+            {
+                debug::write_line(&[
+                    "version `",
+                    required.name,
+                    "' not found (required by ",
+                    shared_object.display_name(),
+                    ")",
+                ]);
+            }
+        }
+    }
+}
@@ -13,14 +13,26 @@ pub const DT_RELAENT: usize = 9;
 pub const DT_SYMENT: usize = 11;
 pub const DT_INIT: usize = 12;
 pub const DT_FINI: usize = 13;
+pub const DT_SONAME: usize = 14;
+pub const DT_RPATH: usize = 15;
 pub const DT_REL: usize = 17;
+pub const DT_PLTREL: usize = 20;
+pub const DT_DEBUG: usize = 21;
 pub const DT_TEXTREL: usize = 22;
+pub const DT_JMPREL: usize = 23;
 pub const DT_INIT_ARRAY: usize = 25;
 pub const DT_FINI_ARRAY: usize = 26;
 pub const DT_INIT_ARRAYSZ: usize = 27;
 pub const DT_FINI_ARRAYSZ: usize = 28;
+pub const DT_RUNPATH: usize = 29;
 pub const DT_RELRSZ: usize = 35;
 pub const DT_RELR: usize = 36;
+pub const DT_GNU_HASH: usize = 0x6ffffef5;
+pub const DT_VERSYM: usize = 0x6ffffff0;
+pub const DT_VERDEF: usize = 0x6ffffffc;
+pub const DT_VERDEFNUM: usize = 0x6ffffffd;
+pub const DT_VERNEED: usize = 0x6ffffffe;
+pub const DT_VERNEEDNUM: usize = 0x6fffffff;
 
 /// A union resolved by the d_tag field of the parent dynamic array item.
 #[repr(C)]
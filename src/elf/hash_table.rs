@@ -0,0 +1,371 @@
+/// A symbol hash table, found via `DT_GNU_HASH` or (in older objects) `DT_HASH`.
+///
+/// It lets us find a symbol by name without comparing against every entry in the symbol table.
+#[derive(Clone, Copy)]
+pub enum HashTable {
+    Gnu(GnuHashTable),
+    Sysv(SysvHashTable),
+}
+
+impl HashTable {
+    /// The indexes of the symbols that might be called `name`, the caller still has to compare the names.
+    pub unsafe fn candidates(&self, name: &str) -> HashCandidates {
+        match self {
+            Self::Gnu(table) => table.candidates(name),
+            Self::Sysv(table) => table.candidates(name),
+        }
+    }
+
+    /// How many entries the symbol table has, ELF doesn't record that anywhere else.
+    pub unsafe fn symbol_count(&self) -> usize {
+        match self {
+            Self::Gnu(table) => table.symbol_count(),
+            Self::Sysv(table) => table.chain_count as usize,
+        }
+    }
+}
+
+/// The GNU hash table:
+///
+/// ```no_run
+/// |----------------------------|
+/// | bucket_count: u32          |
+/// | symbol_offset: u32         |
+/// | bloom_size: u32            |
+/// | bloom_shift: u32           |
+/// |----------------------------|
+/// | bloom: [usize; bloom_size] |
+/// |----------------------------|
+/// | buckets: [u32; buckets]    | <- first symbol index in each bucket
+/// |----------------------------|
+/// | chain: [u32; ..]           | <- hash of each symbol from `symbol_offset` on, lowest bit marks the end of a bucket
+/// |----------------------------|
+/// ```
+///
+/// The bloom filter lets us reject most names without touching the buckets at all.
+#[derive(Clone, Copy)]
+pub struct GnuHashTable {
+    bucket_count: u32,
+    symbol_offset: u32,
+    bloom_shift: u32,
+    bloom: &'static [usize],
+    buckets: *const u32,
+    chain: *const u32,
+}
+
+impl GnuHashTable {
+    pub unsafe fn new(pointer: *const u32) -> Self {
+        let (bucket_count, symbol_offset, bloom_size, bloom_shift) =
+            (*pointer, *pointer.add(1), *pointer.add(2), *pointer.add(3));
+        let bloom =
+            core::slice::from_raw_parts(pointer.add(4) as *const usize, bloom_size as usize);
+        let buckets = bloom.as_ptr().add(bloom.len()) as *const u32;
+
+        Self {
+            bucket_count,
+            symbol_offset,
+            bloom_shift,
+            bloom,
+            buckets,
+            chain: buckets.add(bucket_count as usize),
+        }
+    }
+
+    pub fn hash(name: &str) -> u32 {
+        let mut hash: u32 = 5381;
+        for byte in name.bytes() {
+            hash = hash.wrapping_mul(33).wrapping_add(byte as u32);
+        }
+        hash
+    }
+
+    /// Whether the bloom filter says `hash` could be in the table; a `false` is definite.
+    pub fn might_contain(&self, hash: u32) -> bool {
+        const BITS: u32 = usize::BITS;
+        let word = self.bloom[(hash / BITS) as usize % self.bloom.len()];
+        let mask = (1 << (hash % BITS)) | (1 << ((hash >> self.bloom_shift) % BITS));
+        word & mask == mask
+    }
+
+    unsafe fn candidates(&self, name: &str) -> HashCandidates {
+        let hash = Self::hash(name);
+        if self.bucket_count == 0 {
+            return HashCandidates::Empty;
+        }
+        if !self.might_contain(hash) {
+            return HashCandidates::Rejected;
+        }
+
+        let index = *self.buckets.add((hash % self.bucket_count) as usize) as usize;
+        if index < self.symbol_offset as usize {
+            return HashCandidates::Empty;
+        }
+        HashCandidates::Gnu {
+            table: *self,
+            hash,
+            index,
+        }
+    }
+
+    unsafe fn symbol_count(&self) -> usize {
+        // The last symbol is at the end of the highest bucket's chain:
+        let mut last = 0;
+        for bucket in 0..self.bucket_count as usize {
+            last = last.max(*self.buckets.add(bucket) as usize);
+        }
+        if last < self.symbol_offset as usize {
+            return self.symbol_offset as usize;
+        }
+        while *self.chain.add(last - self.symbol_offset as usize) & 1 == 0 {
+            last += 1;
+        }
+        last + 1
+    }
+}
+
+/// The original System V hash table:
+///
+/// ```no_run
+/// |----------------------------|
+/// | bucket_count: u32          |
+/// | chain_count: u32           | <- the same as the number of symbols
+/// |----------------------------|
+/// | buckets: [u32; buckets]    | <- first symbol index in each bucket
+/// |----------------------------|
+/// | chain: [u32; chain_count]  | <- next symbol index in the same bucket, 0 ends it
+/// |----------------------------|
+/// ```
+#[derive(Clone, Copy)]
+pub struct SysvHashTable {
+    bucket_count: u32,
+    chain_count: u32,
+    buckets: *const u32,
+    chain: *const u32,
+}
+
+impl SysvHashTable {
+    pub unsafe fn new(pointer: *const u32) -> Self {
+        let (bucket_count, chain_count) = (*pointer, *pointer.add(1));
+        Self {
+            bucket_count,
+            chain_count,
+            buckets: pointer.add(2),
+            chain: pointer.add(2 + bucket_count as usize),
+        }
+    }
+
+    pub fn hash(name: &str) -> u32 {
+        let mut hash: u32 = 0;
+        for byte in name.bytes() {
+            hash = (hash << 4).wrapping_add(byte as u32);
+            let high = hash & 0xf0000000;
+            hash ^= high >> 24;
+            hash &= !high;
+        }
+        hash
+    }
+
+    unsafe fn candidates(&self, name: &str) -> HashCandidates {
+        if self.bucket_count == 0 {
+            return HashCandidates::Empty;
+        }
+        let bucket = Self::hash(name) % self.bucket_count;
+        HashCandidates::Sysv {
+            table: *self,
+            index: *self.buckets.add(bucket as usize) as usize,
+        }
+    }
+}
+
+/// An iterator over the symbol indexes in the bucket a name hashes to.
+pub enum HashCandidates {
+    Gnu {
+        table: GnuHashTable,
+        hash: u32,
+        index: usize,
+    },
+    Sysv {
+        table: SysvHashTable,
+        index: usize,
+    },
+    Empty,
+    /// Nothing, and the bloom filter knew without looking at the buckets.
+    Rejected,
+}
+
+impl Iterator for HashCandidates {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self {
+                Self::Gnu { table, hash, index } => {
+                    let (symbol_index, hash) = (*index, *hash);
+                    let chain_hash =
+                        unsafe { *table.chain.add(symbol_index - table.symbol_offset as usize) };
+                    // The lowest bit marks the last symbol in the bucket:
+                    if chain_hash & 1 == 1 {
+                        *self = Self::Empty;
+                    } else {
+                        *index += 1;
+                    }
+                    if chain_hash | 1 == hash | 1 {
+                        return Some(symbol_index);
+                    }
+                }
+                Self::Sysv { table, index } => {
+                    let symbol_index = *index;
+                    // Index 0 is the null symbol, so it doubles as the end of the chain:
+                    if symbol_index == 0 || symbol_index >= table.chain_count as usize {
+                        *self = Self::Empty;
+                        return None;
+                    }
+                    *index = unsafe { *table.chain.add(symbol_index) } as usize;
+                    return Some(symbol_index);
+                }
+                Self::Empty | Self::Rejected => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Where the symbols in the tables below start, index 0 is always the null symbol.
+    const SYMBOL_OFFSET: u32 = 1;
+    const BLOOM_SHIFT: u32 = 6;
+
+    /// Lays out a GNU hash table for `names` the way a linker would, with a single word of bloom filter.
+    ///
+    /// Returns the table, and the names in symbol table order (the linker sorts them by bucket).
+    fn gnu_table(names: &[&'static str], bucket_count: u32) -> (Vec<usize>, Vec<&'static str>) {
+        let bucket_of = |name: &str| GnuHashTable::hash(name) % bucket_count;
+        let mut symbols = names.to_vec();
+        symbols.sort_by_key(|name| bucket_of(name));
+
+        let mut bloom = 0usize;
+        let mut buckets = vec![0; bucket_count as usize];
+        let mut chain = Vec::new();
+        for (index, name) in symbols.iter().enumerate().rev() {
+            let hash = GnuHashTable::hash(name);
+            bloom |= 1 << (hash % usize::BITS) | 1 << ((hash >> BLOOM_SHIFT) % usize::BITS);
+            let last_in_bucket = buckets[bucket_of(name) as usize] == 0;
+            buckets[bucket_of(name) as usize] = SYMBOL_OFFSET + index as u32;
+            chain.insert(0, hash & !1 | last_in_bucket as u32);
+        }
+
+        let mut words = vec![bucket_count, SYMBOL_OFFSET, 1, BLOOM_SHIFT];
+        words.extend([bloom as u32, (bloom >> 32) as u32]);
+        words.extend(buckets);
+        words.extend(chain);
+        (to_table(&words), symbols)
+    }
+
+    /// Lays out a System V hash table for `names`, in the order given.
+    fn sysv_table(names: &[&str], bucket_count: u32) -> Vec<usize> {
+        let chain_count = SYMBOL_OFFSET as usize + names.len();
+        let mut buckets = vec![0; bucket_count as usize];
+        let mut chain = vec![0; chain_count];
+        for (index, name) in names.iter().enumerate() {
+            let (symbol_index, bucket) = (
+                SYMBOL_OFFSET + index as u32,
+                (SysvHashTable::hash(name) % bucket_count) as usize,
+            );
+            chain[symbol_index as usize] = buckets[bucket];
+            buckets[bucket] = symbol_index;
+        }
+
+        let mut words = vec![bucket_count, chain_count as u32];
+        words.extend(buckets);
+        words.extend(chain);
+        to_table(&words)
+    }
+
+    /// The bloom filter is read as `usize`s, so the table has to be aligned like them.
+    fn to_table(words: &[u32]) -> Vec<usize> {
+        let mut table = vec![0usize; words.len().div_ceil(2)];
+        unsafe {
+            core::ptr::copy_nonoverlapping(words.as_ptr(), table.as_mut_ptr().cast(), words.len())
+        };
+        table
+    }
+
+    #[test]
+    fn hashes_match_the_reference_values() {
+        assert_eq!(GnuHashTable::hash(""), 0x00001505);
+        assert_eq!(GnuHashTable::hash("printf"), 0x156b2bb8);
+        assert_eq!(GnuHashTable::hash("exit"), 0x7c967e3f);
+        assert_eq!(GnuHashTable::hash("syscall"), 0xbac212a0);
+
+        assert_eq!(SysvHashTable::hash(""), 0);
+        assert_eq!(SysvHashTable::hash("printf"), 0x077905a6);
+        assert_eq!(SysvHashTable::hash("exit"), 0x0006cf04);
+        assert_eq!(SysvHashTable::hash("syscall"), 0x0b09985c);
+    }
+
+    #[test]
+    fn gnu_lookup_finds_every_symbol() {
+        let names = ["malloc", "free", "calloc", "realloc"];
+        let (memory, symbols) = gnu_table(&names, 3);
+        let table = HashTable::Gnu(unsafe { GnuHashTable::new(memory.as_ptr().cast()) });
+
+        for name in names {
+            let index =
+                SYMBOL_OFFSET as usize + symbols.iter().position(|&symbol| symbol == name).unwrap();
+            let candidates: Vec<usize> = unsafe { table.candidates(name) }.collect();
+            assert!(
+                candidates.contains(&index),
+                "{name} should be at {index}, got {candidates:?}"
+            );
+        }
+        assert_eq!(
+            unsafe { table.symbol_count() },
+            SYMBOL_OFFSET as usize + names.len()
+        );
+    }
+
+    #[test]
+    fn gnu_bloom_filter_rejects_without_looking() {
+        let (memory, _) = gnu_table(&["malloc", "free", "calloc", "realloc"], 3);
+        let table = unsafe { GnuHashTable::new(memory.as_ptr().cast()) };
+
+        for name in ["malloc", "free", "calloc", "realloc"] {
+            assert!(table.might_contain(GnuHashTable::hash(name)));
+        }
+        // None of `printf`'s bits are set:
+        assert!(!table.might_contain(GnuHashTable::hash("printf")));
+        assert!(matches!(
+            unsafe { table.candidates("printf") },
+            HashCandidates::Rejected
+        ));
+    }
+
+    #[test]
+    fn gnu_table_without_buckets_is_empty() {
+        let (memory, _) = gnu_table(&[], 0);
+        let table = unsafe { GnuHashTable::new(memory.as_ptr().cast()) };
+        assert_eq!(unsafe { table.candidates("malloc") }.count(), 0);
+    }
+
+    #[test]
+    fn sysv_lookup_follows_the_chains() {
+        // More names than buckets, so some share one:
+        let names = ["malloc", "free", "calloc", "realloc", "printf"];
+        let memory = sysv_table(&names, 2);
+        let table = HashTable::Sysv(unsafe { SysvHashTable::new(memory.as_ptr().cast()) });
+
+        for (index, name) in names.iter().enumerate() {
+            let candidates: Vec<usize> = unsafe { table.candidates(name) }.collect();
+            assert!(candidates.contains(&(SYMBOL_OFFSET as usize + index)));
+            // The null symbol ends a chain, it's never a candidate:
+            assert!(!candidates.contains(&0));
+        }
+        assert_eq!(
+            unsafe { table.symbol_count() },
+            SYMBOL_OFFSET as usize + names.len()
+        );
+    }
+}
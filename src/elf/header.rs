@@ -1,3 +1,4 @@
+pub const ET_EXEC: u16 = 2;
 pub const ET_DYN: u16 = 3;
 
 #[repr(C)]
@@ -1,8 +1,10 @@
 pub mod dynamic_array;
+pub mod hash_table;
 pub mod header;
 pub mod header_table;
 pub mod program_header;
 pub mod relocate;
 pub mod string_table;
 pub mod symbol;
+pub mod symbol_version;
 pub mod thread_local_storage;
@@ -11,6 +11,15 @@ pub trait Relocatable {
 #[derive(Clone, Copy)]
 pub struct RelocationSlices {
     pub rela_slice: &'static [Rela],
+    /// The PLT relocations (`DT_JMPREL`), which glibc may bind lazily.
+    pub plt_rela_slice: &'static [Rela],
+}
+
+impl RelocationSlices {
+    /// Every relocation, the PLT ones last.
+    pub fn iter(&self) -> impl Iterator<Item = &'static Rela> {
+        self.rela_slice.iter().chain(self.plt_rela_slice)
+    }
 }
 
 /// An ELF relocation entry with an addend.
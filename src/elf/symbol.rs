@@ -1,3 +1,29 @@
+// Symbol Bindings (`st_info >> 4`):
+pub const STB_LOCAL: u8 = 0;
+pub const STB_GLOBAL: u8 = 1;
+pub const STB_WEAK: u8 = 2;
+pub const STB_GNU_UNIQUE: u8 = 10;
+
+// Symbol Types (`st_info & 0xf`):
+pub const STT_NOTYPE: u8 = 0;
+pub const STT_OBJECT: u8 = 1;
+pub const STT_FUNC: u8 = 2;
+pub const STT_SECTION: u8 = 3;
+pub const STT_FILE: u8 = 4;
+pub const STT_COMMON: u8 = 5;
+pub const STT_TLS: u8 = 6;
+pub const STT_GNU_IFUNC: u8 = 10;
+
+// Symbol Visibilities (`st_other & 0x3`):
+pub const STV_DEFAULT: u8 = 0;
+pub const STV_INTERNAL: u8 = 1;
+pub const STV_HIDDEN: u8 = 2;
+pub const STV_PROTECTED: u8 = 3;
+
+// Special Section Indexes:
+pub const SHN_UNDEF: u16 = 0;
+pub const SHN_ABS: u16 = 0xfff1;
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct Symbol {
@@ -15,6 +41,67 @@ pub struct Symbol {
     pub st_size: usize,
 }
 
+impl Symbol {
+    pub fn st_bind(&self) -> u8 {
+        self.st_info >> 4
+    }
+
+    pub fn st_type(&self) -> u8 {
+        self.st_info & 0xf
+    }
+
+    pub fn st_visibility(&self) -> u8 {
+        self.st_other & 0x3
+    }
+
+    /// The binding the way `readelf` shows it.
+    pub fn bind_name(&self) -> &'static str {
+        match self.st_bind() {
+            STB_LOCAL => "LOCAL",
+            STB_GLOBAL => "GLOBAL",
+            STB_WEAK => "WEAK",
+            STB_GNU_UNIQUE => "UNIQUE",
+            _ => "<unknown>",
+        }
+    }
+
+    /// The type the way `readelf` shows it.
+    pub fn type_name(&self) -> &'static str {
+        match self.st_type() {
+            STT_NOTYPE => "NOTYPE",
+            STT_OBJECT => "OBJECT",
+            STT_FUNC => "FUNC",
+            STT_SECTION => "SECTION",
+            STT_FILE => "FILE",
+            STT_COMMON => "COMMON",
+            STT_TLS => "TLS",
+            STT_GNU_IFUNC => "IFUNC",
+            _ => "<unknown>",
+        }
+    }
+
+    /// The visibility the way `readelf` shows it.
+    pub fn visibility_name(&self) -> &'static str {
+        match self.st_visibility() {
+            STV_DEFAULT => "DEFAULT",
+            STV_INTERNAL => "INTERNAL",
+            STV_HIDDEN => "HIDDEN",
+            _ => "PROTECTED",
+        }
+    }
+
+    /// Whether this symbol is a definition other objects can bind to, rather than a reference.
+    pub fn is_exported_definition(&self) -> bool {
+        self.st_shndx != SHN_UNDEF
+            && matches!(self.st_bind(), STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+            && matches!(
+                self.st_type(),
+                STT_NOTYPE | STT_OBJECT | STT_FUNC | STT_COMMON | STT_TLS | STT_GNU_IFUNC
+            )
+            && matches!(self.st_visibility(), STV_DEFAULT | STV_PROTECTED)
+    }
+}
+
 pub struct SymbolTable(*const Symbol);
 
 impl SymbolTable {
@@ -0,0 +1,414 @@
+use alloc::vec::Vec;
+use core::ptr::null;
+
+use crate::elf::string_table::StringTable;
+
+/// `vd_flags` of the definition naming the object itself (its soname), rather than a real version.
+pub const VER_FLG_BASE: u16 = 0x1;
+/// `versym` index of a local symbol, it has no version.
+pub const VER_NDX_LOCAL: u16 = 0;
+/// `versym` index of an unversioned global symbol.
+pub const VER_NDX_GLOBAL: u16 = 1;
+/// The bit in a `versym` entry that hides a definition from unversioned references (`symbol@VERSION` rather than `symbol@@VERSION`).
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+
+/// An entry in the `DT_VERDEF` list, one per version this object defines.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VersionDefinition {
+    pub vd_version: u16,
+    pub vd_flags: u16,
+    pub vd_ndx: u16,
+    pub vd_cnt: u16,
+    pub vd_hash: u32,
+    /// Byte offset to the first `VersionDefinitionAuxiliary`, which holds the name.
+    pub vd_aux: u32,
+    /// Byte offset to the next definition, 0 for the last one.
+    pub vd_next: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VersionDefinitionAuxiliary {
+    pub vda_name: u32,
+    pub vda_next: u32,
+}
+
+/// An entry in the `DT_VERNEED` list, one per library this object needs versions from.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VersionRequirement {
+    pub vn_version: u16,
+    pub vn_cnt: u16,
+    pub vn_file: u32,
+    /// Byte offset to the first `VersionRequirementAuxiliary`.
+    pub vn_aux: u32,
+    /// Byte offset to the next requirement, 0 for the last one.
+    pub vn_next: u32,
+}
+
+/// A single version needed from a `VersionRequirement`'s library.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VersionRequirementAuxiliary {
+    pub vna_hash: u32,
+    pub vna_flags: u16,
+    /// The `versym` index symbols needing this version use.
+    pub vna_other: u16,
+    pub vna_name: u32,
+    pub vna_next: u32,
+}
+
+/// A version a symbol reference needs.
+#[derive(Clone, Copy)]
+pub struct RequiredVersion {
+    pub name: &'static str,
+    /// The library that's expected to define it (from `DT_VERNEED`), `None` if it's one of the requester's own versions.
+    pub file: Option<&'static str>,
+}
+
+/// The version a symbol is defined with, from the defining object's `DT_VERDEF`.
+#[derive(Clone, Copy)]
+pub struct DefinedVersion {
+    pub index: u16,
+    /// `None` for `VER_NDX_LOCAL` and `VER_NDX_GLOBAL`.
+    pub name: Option<&'static str>,
+    pub hidden: bool,
+}
+
+impl DefinedVersion {
+    /// Whether a definition with this version satisfies a reference needing `required`.
+    ///
+    /// A versioned reference takes the definition with that version, or an unversioned one; an unversioned reference
+    /// takes the default version (`symbol@@VERSION`), but not the hidden ones (`symbol@VERSION`).
+    pub fn satisfies(&self, required: Option<&RequiredVersion>) -> bool {
+        match required {
+            Some(required) => {
+                self.name == Some(required.name) || (self.name.is_none() && !self.hidden)
+            }
+            None => !self.hidden,
+        }
+    }
+}
+
+/// The GNU symbol versioning tables of an object.
+///
+/// `DT_VERSYM` runs parallel to the symbol table, giving each symbol an index that's either a `vd_ndx` from
+/// `DT_VERDEF` (for definitions) or a `vna_other` from `DT_VERNEED` (for references).
+pub struct SymbolVersions {
+    pub versym: *const u16,
+    pub verdef: *const VersionDefinition,
+    pub verdef_count: usize,
+    pub verneed: *const VersionRequirement,
+    pub verneed_count: usize,
+}
+
+impl SymbolVersions {
+    pub const fn none() -> Self {
+        Self {
+            versym: null(),
+            verdef: null(),
+            verdef_count: 0,
+            verneed: null(),
+            verneed_count: 0,
+        }
+    }
+
+    /// Whether this object uses symbol versioning at all.
+    pub fn is_versioned(&self) -> bool {
+        !self.versym.is_null()
+    }
+
+    /// The version a reference to the symbol at `symbol_index` needs, if any.
+    ///
+    /// NOTE: An object referring to its own symbols uses the index of its own definition, so `DT_VERDEF` counts too.
+    pub unsafe fn required(
+        &self,
+        symbol_index: usize,
+        string_table: &StringTable,
+    ) -> Option<RequiredVersion> {
+        if self.versym.is_null() {
+            return None;
+        }
+        let index = *self.versym.add(symbol_index) & !VERSYM_HIDDEN;
+        if index <= VER_NDX_GLOBAL {
+            return None;
+        }
+
+        let mut requirement = self.verneed;
+        for _ in 0..self.verneed_count {
+            let mut auxiliary = requirement.byte_add((*requirement).vn_aux as usize)
+                as *const VersionRequirementAuxiliary;
+            for _ in 0..(*requirement).vn_cnt {
+                if (*auxiliary).vna_other == index {
+                    return Some(RequiredVersion {
+                        name: string_table.get((*auxiliary).vna_name as usize),
+                        file: Some(string_table.get((*requirement).vn_file as usize)),
+                    });
+                }
+                auxiliary = auxiliary.byte_add((*auxiliary).vna_next as usize);
+            }
+            requirement = requirement.byte_add((*requirement).vn_next as usize);
+        }
+
+        let name = self.definition_name(index, string_table)?;
+        Some(RequiredVersion { name, file: None })
+    }
+
+    /// Every version this object needs from its libraries (`DT_VERNEED`).
+    pub unsafe fn requirements(&self, string_table: &StringTable) -> Vec<RequiredVersion> {
+        let mut requirements = Vec::new();
+        let mut requirement = self.verneed;
+        for _ in 0..self.verneed_count {
+            let mut auxiliary = requirement.byte_add((*requirement).vn_aux as usize)
+                as *const VersionRequirementAuxiliary;
+            for _ in 0..(*requirement).vn_cnt {
+                requirements.push(RequiredVersion {
+                    name: string_table.get((*auxiliary).vna_name as usize),
+                    file: Some(string_table.get((*requirement).vn_file as usize)),
+                });
+                auxiliary = auxiliary.byte_add((*auxiliary).vna_next as usize);
+            }
+            requirement = requirement.byte_add((*requirement).vn_next as usize);
+        }
+        requirements
+    }
+
+    /// Whether this object defines the version `name` (`DT_VERDEF`).
+    pub unsafe fn defines(&self, name: &str, string_table: &StringTable) -> bool {
+        let mut definition = self.verdef;
+        for _ in 0..self.verdef_count {
+            let auxiliary = definition.byte_add((*definition).vd_aux as usize)
+                as *const VersionDefinitionAuxiliary;
+            if string_table.get((*auxiliary).vda_name as usize) == name {
+                return true;
+            }
+            definition = definition.byte_add((*definition).vd_next as usize);
+        }
+        false
+    }
+
+    /// The version the symbol at `symbol_index` is defined with, `None` if this object isn't versioned.
+    pub unsafe fn defined(
+        &self,
+        symbol_index: usize,
+        string_table: &StringTable,
+    ) -> Option<DefinedVersion> {
+        if self.versym.is_null() {
+            return None;
+        }
+        let entry = *self.versym.add(symbol_index);
+        let index = entry & !VERSYM_HIDDEN;
+        Some(DefinedVersion {
+            index,
+            name: self.definition_name(index, string_table),
+            hidden: entry & VERSYM_HIDDEN != 0,
+        })
+    }
+
+    /// The name of the version definition with `vd_ndx == index`.
+    unsafe fn definition_name(
+        &self,
+        index: u16,
+        string_table: &StringTable,
+    ) -> Option<&'static str> {
+        if index <= VER_NDX_GLOBAL {
+            return None;
+        }
+
+        let mut definition = self.verdef;
+        for _ in 0..self.verdef_count {
+            if (*definition).vd_ndx == index && (*definition).vd_flags & VER_FLG_BASE == 0 {
+                let auxiliary = definition.byte_add((*definition).vd_aux as usize)
+                    as *const VersionDefinitionAuxiliary;
+                return Some(string_table.get((*auxiliary).vda_name as usize));
+            }
+            definition = definition.byte_add((*definition).vd_next as usize);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+
+    /// `libfoo.so`'s strings, and where each one starts.
+    const STRINGS: &[u8] = b"\0libfoo.so\0FOO_1\0FOO_2\0libc.so.6\0GLIBC_2.2.5\0GLIBC_2.34\0";
+    const LIBFOO: u32 = 1;
+    const FOO_1: u32 = 11;
+    const FOO_2: u32 = 17;
+    const LIBC: u32 = 23;
+    const GLIBC_2_2_5: u32 = 33;
+    const GLIBC_2_34: u32 = 45;
+
+    /// A definition is followed by its (single) name, like a linker lays them out.
+    #[repr(C)]
+    struct Definition(VersionDefinition, VersionDefinitionAuxiliary);
+
+    #[repr(C)]
+    struct Requirement(VersionRequirement, [VersionRequirementAuxiliary; 2]);
+
+    fn definition(index: u16, flags: u16, name: u32, last: bool) -> Definition {
+        let size = size_of::<Definition>() as u32;
+        Definition(
+            VersionDefinition {
+                vd_version: 1,
+                vd_flags: flags,
+                vd_ndx: index,
+                vd_cnt: 1,
+                vd_hash: 0,
+                vd_aux: size_of::<VersionDefinition>() as u32,
+                vd_next: if last { 0 } else { size },
+            },
+            VersionDefinitionAuxiliary {
+                vda_name: name,
+                vda_next: 0,
+            },
+        )
+    }
+
+    fn needed(index: u16, name: u32, last: bool) -> VersionRequirementAuxiliary {
+        VersionRequirementAuxiliary {
+            vna_hash: 0,
+            vna_flags: 0,
+            vna_other: index,
+            vna_name: name,
+            vna_next: if last {
+                0
+            } else {
+                size_of::<VersionRequirementAuxiliary>() as u32
+            },
+        }
+    }
+
+    /// The versions of `libfoo.so`, which defines `FOO_1` and `FOO_2` and needs two versions of `libc.so.6`.
+    ///
+    /// Its symbols are: local, unversioned, `@FOO_1`, `@@FOO_2`, and references to `@GLIBC_2.2.5` and `@GLIBC_2.34`.
+    fn libfoo() -> (SymbolVersions, StringTable) {
+        let definitions: &'static [Definition] = Box::leak(Box::new([
+            definition(1, VER_FLG_BASE, LIBFOO, false),
+            definition(2, 0, FOO_1, false),
+            definition(3, 0, FOO_2, true),
+        ]));
+        let requirement: &'static Requirement = Box::leak(Box::new(Requirement(
+            VersionRequirement {
+                vn_version: 1,
+                vn_cnt: 2,
+                vn_file: LIBC,
+                vn_aux: size_of::<VersionRequirement>() as u32,
+                vn_next: 0,
+            },
+            [needed(4, GLIBC_2_2_5, false), needed(5, GLIBC_2_34, true)],
+        )));
+        let versym: &'static [u16] = Box::leak(Box::new([
+            VER_NDX_LOCAL,
+            VER_NDX_GLOBAL,
+            2 | VERSYM_HIDDEN,
+            3,
+            4,
+            5,
+        ]));
+
+        let versions = SymbolVersions {
+            versym: versym.as_ptr(),
+            verdef: definitions.as_ptr().cast(),
+            verdef_count: definitions.len(),
+            verneed: &requirement.0,
+            verneed_count: 1,
+        };
+        (versions, StringTable::new(STRINGS.as_ptr()))
+    }
+
+    #[test]
+    fn references_need_the_version_from_their_library() {
+        let (versions, strings) = libfoo();
+        let required = unsafe { versions.required(4, &strings) }.unwrap();
+        assert_eq!(
+            (required.name, required.file),
+            ("GLIBC_2.2.5", Some("libc.so.6"))
+        );
+        let required = unsafe { versions.required(5, &strings) }.unwrap();
+        assert_eq!(
+            (required.name, required.file),
+            ("GLIBC_2.34", Some("libc.so.6"))
+        );
+
+        // References to its own symbols need its own versions:
+        let required = unsafe { versions.required(3, &strings) }.unwrap();
+        assert_eq!((required.name, required.file), ("FOO_2", None));
+
+        assert!(unsafe { versions.required(VER_NDX_LOCAL as usize, &strings) }.is_none());
+        assert!(unsafe { versions.required(VER_NDX_GLOBAL as usize, &strings) }.is_none());
+    }
+
+    #[test]
+    fn every_requirement_is_listed() {
+        let (versions, strings) = libfoo();
+        let requirements = unsafe { versions.requirements(&strings) };
+        let requirements: Vec<_> = requirements
+            .iter()
+            .map(|required| (required.name, required.file))
+            .collect();
+        assert_eq!(
+            requirements,
+            [
+                ("GLIBC_2.2.5", Some("libc.so.6")),
+                ("GLIBC_2.34", Some("libc.so.6"))
+            ]
+        );
+    }
+
+    #[test]
+    fn definitions_know_whether_they_are_the_default() {
+        let (versions, strings) = libfoo();
+        assert!(unsafe { versions.defines("FOO_1", &strings) });
+        assert!(!unsafe { versions.defines("GLIBC_2.34", &strings) });
+
+        let hidden = unsafe { versions.defined(2, &strings) }.unwrap();
+        assert_eq!((hidden.name, hidden.hidden), (Some("FOO_1"), true));
+        let default = unsafe { versions.defined(3, &strings) }.unwrap();
+        assert_eq!((default.name, default.hidden), (Some("FOO_2"), false));
+        // The base definition names the object, it isn't a version anything can ask for:
+        let global = unsafe { versions.defined(VER_NDX_GLOBAL as usize, &strings) }.unwrap();
+        assert_eq!((global.name, global.hidden), (None, false));
+    }
+
+    #[test]
+    fn unversioned_references_only_take_the_default_version() {
+        let (versions, strings) = libfoo();
+        let defined = |symbol_index| unsafe { versions.defined(symbol_index, &strings) }.unwrap();
+
+        // `symbol@@FOO_2` and plain `symbol` do, `symbol@FOO_1` doesn't:
+        assert!(defined(3).satisfies(None));
+        assert!(defined(1).satisfies(None));
+        assert!(!defined(2).satisfies(None));
+    }
+
+    #[test]
+    fn versioned_references_take_exactly_their_version() {
+        let (versions, strings) = libfoo();
+        let defined = |symbol_index| unsafe { versions.defined(symbol_index, &strings) }.unwrap();
+        let foo_1 = RequiredVersion {
+            name: "FOO_1",
+            file: Some("libfoo.so"),
+        };
+
+        // Hidden or not, the version asked for is the one that binds:
+        assert!(defined(2).satisfies(Some(&foo_1)));
+        assert!(!defined(3).satisfies(Some(&foo_1)));
+        // An unversioned definition satisfies any version:
+        assert!(defined(1).satisfies(Some(&foo_1)));
+    }
+
+    #[test]
+    fn unversioned_objects_have_no_versions() {
+        let versions = SymbolVersions::none();
+        let strings = StringTable::new(STRINGS.as_ptr());
+        assert!(!versions.is_versioned());
+        assert!(unsafe { versions.required(1, &strings) }.is_none());
+        assert!(unsafe { versions.defined(1, &strings) }.is_none());
+        assert!(unsafe { versions.requirements(&strings) }.is_empty());
+    }
+}
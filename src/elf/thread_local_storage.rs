@@ -13,4 +13,6 @@ pub struct ThreadControlBlock {
 pub union DynamicThreadVectorItem {
     pub pointer: *mut c_void,
     pub generation_counter: usize,
+    /// Like glibc's, the entry right before the generation counter is how many modules the vector has room for.
+    pub length: usize,
 }
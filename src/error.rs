@@ -0,0 +1,193 @@
+use alloc::string::String;
+
+use crate::{
+    arch::{
+        exit, io,
+        relocation::relocation_name,
+        syscall::{
+            Errno, EACCES, EEXIST, EINVAL, EIO, EISDIR, ELOOP, EMFILE, ENAMETOOLONG, ENFILE,
+            ENOENT, ENOEXEC, ENOMEM, ENOTDIR, EPERM,
+        },
+    },
+    sync::OnceLock,
+    syscall_format,
+};
+
+/// What `glibc` exits with when the program can't be loaded, shells use the same status for "command not found".
+pub const EXIT_LOAD_FAILURE: usize = 127;
+
+/// Everything that can stop a program from being loaded.
+///
+/// The fields borrow rather than own, so reporting an error doesn't need the allocator; that matters for the ones that
+/// can happen before we are relocated.
+pub enum LoaderError<'a> {
+    /// A library (or the program) couldn't be opened, `name` is what was asked for.
+    CannotOpen { name: &'a str, errno: Errno },
+    /// The headers of an opened file couldn't be read.
+    CannotRead { path: &'a str, errno: Errno },
+    /// Mapping (or reserving space for) an object failed.
+    CannotMap { path: &'a str, errno: Errno },
+    /// There was no memory for our own thread local storage.
+    CannotAllocateTls { errno: Errno },
+    /// A non-PIE executable has to go where something is already mapped.
+    AddressConflict {
+        path: &'a str,
+        start: usize,
+        end: usize,
+        /// Where we are, if we are what's in the way; `None` when the kernel refused because of some other mapping.
+        miros: Option<(usize, usize)>,
+    },
+    /// Nothing defines a symbol a relocation needs.
+    UndefinedSymbol {
+        path: &'a str,
+        symbol: &'a str,
+        version: Option<&'a str>,
+    },
+    /// A relocation type we don't know how to apply.
+    UnsupportedRelocation { path: &'a str, relocation_type: u32 },
+}
+
+impl LoaderError<'_> {
+    /// What went wrong, the part of the message `glibc` puts before the details.
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::UndefinedSymbol { .. } => "symbol lookup error",
+            Self::UnsupportedRelocation { .. } => "relocation error",
+            _ => "error while loading shared libraries",
+        }
+    }
+
+    /// Hands the message to `write` piece by piece, it reads exactly like `glibc`'s (and `dlerror`'s):
+    ///
+    /// ```text
+    /// libfoo.so.1: cannot open shared object file: No such file or directory
+    /// ./program: undefined symbol: compress2, version ZLIB_1.2.0
+    /// ```
+    ///
+    /// NOTE: `write` isn't a trait object, vtables need relocating and this has to work before we are relocated.
+    fn write_message(&self, mut write: impl FnMut(&str)) {
+        match *self {
+            Self::CannotOpen { name, errno } => {
+                write(name);
+                write(": cannot open shared object file: ");
+                write(strerror(errno));
+            }
+            Self::CannotRead { path, errno } => {
+                write(path);
+                write(": cannot read file data: ");
+                write(strerror(errno));
+            }
+            Self::CannotMap { path, errno } => {
+                write(path);
+                write(": failed to map segment from shared object: ");
+                write(strerror(errno));
+            }
+            Self::CannotAllocateTls { errno } => {
+                write("cannot allocate memory for thread-local data: ");
+                write(strerror(errno));
+            }
+            Self::AddressConflict {
+                path,
+                start,
+                end,
+                miros,
+            } => {
+                write(path);
+                write(": cannot map non-PIE executable at 0x");
+                write(syscall_format::hex(start as u64, &mut [0; 16]));
+                write("-0x");
+                write(syscall_format::hex(end as u64, &mut [0; 16]));
+                match miros {
+                    Some((miros_start, miros_end)) => {
+                        write(", it overlaps miros at 0x");
+                        write(syscall_format::hex(miros_start as u64, &mut [0; 16]));
+                        write("-0x");
+                        write(syscall_format::hex(miros_end as u64, &mut [0; 16]));
+                    }
+                    None => write(", the address range is already in use"),
+                }
+            }
+            Self::UndefinedSymbol {
+                path,
+                symbol,
+                version,
+            } => {
+                write(path);
+                write(": undefined symbol: ");
+                write(symbol);
+                if let Some(version) = version {
+                    write(", version ");
+                    write(version);
+                }
+            }
+            Self::UnsupportedRelocation {
+                path,
+                relocation_type,
+            } => {
+                write(path);
+                write(": unexpected reloc type ");
+                match relocation_name(relocation_type) {
+                    Some(name) => write(name),
+                    None => {
+                        write("0x");
+                        write(syscall_format::hex(relocation_type as u64, &mut [0; 16]));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The message alone, the way `dlerror` returns it.
+    pub fn message(&self) -> String {
+        let mut message = String::new();
+        self.write_message(|part| message.push_str(part));
+        message
+    }
+
+    /// Reports the error on standard error, and exits with `EXIT_LOAD_FAILURE`:
+    ///
+    /// ```text
+    /// ./program: error while loading shared libraries: libfoo.so.1: cannot open shared object file: No such file or directory
+    /// ```
+    pub fn exit(&self) -> ! {
+        io::write(io::STD_ERR, program_name());
+        io::write(io::STD_ERR, ": ");
+        io::write(io::STD_ERR, self.kind());
+        io::write(io::STD_ERR, ": ");
+        self.write_message(|part| io::write(io::STD_ERR, part));
+        io::write(io::STD_ERR, "\n");
+        exit::exit(EXIT_LOAD_FAILURE);
+    }
+}
+
+/// The text `strerror` has for `errno`, for the ones loading can run into.
+pub fn strerror(errno: Errno) -> &'static str {
+    match errno {
+        EPERM => "Operation not permitted",
+        ENOENT => "No such file or directory",
+        EIO => "Input/output error",
+        ENOEXEC => "Exec format error",
+        ENOMEM => "Cannot allocate memory",
+        EACCES => "Permission denied",
+        ENOTDIR => "Not a directory",
+        EISDIR => "Is a directory",
+        EINVAL => "Invalid argument",
+        ENFILE => "Too many open files in system",
+        EMFILE => "Too many open files",
+        ENAMETOOLONG => "File name too long",
+        EEXIST => "File exists",
+        ELOOP => "Too many levels of symbolic links",
+        _ => "Unknown error",
+    }
+}
+
+/// The name errors are reported under, the program's (like `glibc`) once we know it.
+static PROGRAM_NAME: OnceLock<&'static str> = OnceLock::new();
+
+pub fn set_program_name(name: &'static str) {
+    let _ = PROGRAM_NAME.set(name);
+}
+
+fn program_name() -> &'static str {
+    PROGRAM_NAME.get().copied().unwrap_or("miros")
+}
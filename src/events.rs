@@ -0,0 +1,139 @@
+use core::{
+    fmt::Write as _,
+    sync::atomic::{AtomicI32, Ordering},
+};
+
+use crate::{
+    arch::{io, syscall},
+    json,
+    linux::environment_variables::EnvironmentIter,
+    shared_object::SharedObject,
+};
+
+/// Bumped whenever an event or field is removed or changes meaning; adding new ones doesn't.
+pub const SCHEMA_VERSION: usize = 1;
+
+/// The inherited file descriptor `MIROS_EVENTS_FD` names, -1 if it's unset.
+static EVENTS_FILE_DESCRIPTOR: AtomicI32 = AtomicI32::new(-1);
+
+/// A field's value. Addresses are written as hex strings, JSON numbers can't hold all of them precisely.
+pub enum Value<'a> {
+    String(&'a str),
+    Number(usize),
+    Address(usize),
+    Bool(bool),
+    Null,
+}
+
+/// Reads `MIROS_EVENTS_FD`, the file descriptor to write the event stream to.
+pub fn initialize(environment: EnvironmentIter, secure: bool) {
+    // NOTE: Like `LD_DEBUG`, whoever started a privileged program doesn't get to watch what it loads.
+    if secure {
+        return;
+    }
+    for (name, value) in environment {
+        if name != "MIROS_EVENTS_FD" {
+            continue;
+        }
+        let Ok(file_descriptor) = value.parse::<i32>() else {
+            io::write(
+                io::STD_ERR,
+                "warning: MIROS_EVENTS_FD is not a file descriptor: ",
+            );
+            io::write(io::STD_ERR, value);
+            io::write(io::STD_ERR, "\n");
+            continue;
+        };
+        if file_descriptor >= 0 {
+            EVENTS_FILE_DESCRIPTOR.store(file_descriptor, Ordering::Relaxed);
+            emit("start", &[("schema", Value::String("miros-events"))]);
+        }
+    }
+}
+
+pub fn is_enabled() -> bool {
+    EVENTS_FILE_DESCRIPTOR.load(Ordering::Relaxed) >= 0
+}
+
+/// Writes one event as a line of JSON, with the fields every event has first:
+///
+/// ```text
+/// {"v":1,"event":"object_mapped","pid":1234,"path":"/lib/x86_64-linux-gnu/libc.so.6","base":"0x7f...",...}
+/// ```
+///
+/// The events, and the fields each one adds:
+/// - `start`: `schema`, always `"miros-events"`; the first event in the stream.
+/// - `search_attempt`: `library`, `path`, `found`; a file we tried while searching for a library.
+/// - `search_result`: `library`, `requester`, `path` (`null` if it wasn't found).
+/// - `object_mapped`: `path` (empty for the executable the kernel mapped), `base`, `start`, `end`, `build_id` (hex,
+///   `null` if it has none).
+/// - `symbol_bound`: `symbol`, `version` (`null` if unversioned), `from`, `to`.
+/// - `error`: `message`, `object` (what it's about).
+///
+/// NOTE: `init_started`/`init_finished`, `dlopen` and `dlclose` aren't emitted yet, we don't run initializers or have a
+/// `dlopen`. Consumers can't count on them until the schema lists them here.
+pub fn emit(event: &str, fields: &[(&str, Value)]) {
+    let file_descriptor = EVENTS_FILE_DESCRIPTOR.load(Ordering::Relaxed);
+    if file_descriptor < 0 {
+        return;
+    }
+
+    let mut line = format!(
+        "{{\"v\":{SCHEMA_VERSION},\"event\":\"{event}\",\"pid\":{}",
+        syscall::getpid()
+    );
+    for (name, value) in fields {
+        let _ = write!(line, ",\"{name}\":");
+        match value {
+            Value::String(string) => json::push_string(&mut line, string),
+            Value::Number(number) => {
+                let _ = write!(line, "{number}");
+            }
+            Value::Address(address) => {
+                let _ = write!(line, "\"{address:#x}\"");
+            }
+            Value::Bool(bool) => {
+                let _ = write!(line, "{bool}");
+            }
+            Value::Null => line.push_str("null"),
+        }
+    }
+    line.push_str("}\n");
+    // The whole event goes out in one write, so events from different processes sharing the descriptor don't mix:
+    io::write(file_descriptor, &line);
+}
+
+/// `Value::String`, or `Value::Null` for `None`.
+pub fn optional(string: Option<&str>) -> Value<'_> {
+    string.map_or(Value::Null, Value::String)
+}
+
+/// Emits `object_mapped` for `shared_object`.
+pub unsafe fn object_mapped(shared_object: &SharedObject) {
+    if !is_enabled() {
+        return;
+    }
+    let (start, end) = shared_object.mapped_bounds();
+    let build_id = shared_object.build_id_hex();
+    emit(
+        "object_mapped",
+        &[
+            ("path", Value::String(shared_object.path())),
+            ("base", Value::Address(shared_object.base.addr())),
+            ("start", Value::Address(start)),
+            ("end", Value::Address(end)),
+            ("build_id", optional(build_id.as_deref())),
+        ],
+    );
+}
+
+/// Emits `error`, `object` is the file or library name it's about.
+pub fn error(message: &str, object: &str) {
+    emit(
+        "error",
+        &[
+            ("message", Value::String(message)),
+            ("object", Value::String(object)),
+        ],
+    );
+}
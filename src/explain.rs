@@ -0,0 +1,295 @@
+use alloc::vec::Vec;
+
+use crate::{
+    arch::{
+        io,
+        relocation::{relocation_name, R_X86_64_COPY},
+    },
+    elf::{
+        relocate::Rela,
+        symbol::{SHN_UNDEF, STB_LOCAL, STB_WEAK},
+    },
+    loaded_objects,
+    loader_lock::LOADER_LOCK,
+    shared_object::SharedObject,
+    symbol_lookup::{self, Binding, Definition},
+};
+
+/// What became of a definition the lookup came across.
+enum Verdict {
+    Bound,
+    NotExported,
+    CopyItself,
+    WrongVersion,
+    Shadowed,
+}
+
+/// A definition of the symbol somewhere in the lookup scope.
+struct Candidate {
+    shared_object: &'static SharedObject,
+    symbol_index: usize,
+    verdict: Verdict,
+}
+
+/// Explains where every reference to `query` (`NAME` or `NAME@VERSION`) binds, and why (`--explain-symbol`):
+///
+/// ```text
+/// `SSL_new' referenced by ./program (R_X86_64_JUMP_SLOT, version OPENSSL_3.0.0 from libssl.so.3)
+///   lookup scope:
+///     ./program
+///     /usr/lib/libfake.so
+///     /usr/lib/x86_64-linux-gnu/libssl.so.3
+///   candidates:
+///     /usr/lib/libfake.so: SSL_new  GLOBAL FUNC DEFAULT  ignored: the version doesn't match
+///     /usr/lib/x86_64-linux-gnu/libssl.so.3: SSL_new@@OPENSSL_3.0.0  GLOBAL FUNC DEFAULT  bound here
+///   rule: version match, earlier definitions in the scope have the wrong version
+/// ```
+///
+/// Returns whether anything referenced it.
+pub unsafe fn explain_symbol(query: &str, interpreter: &SharedObject) -> bool {
+    let (name, version) = match query.split_once('@') {
+        // `NAME@@VERSION` means the same thing here, the reference decides which definitions it can take.
+        Some((name, version)) => (name, Some(version.trim_start_matches('@'))),
+        None => (query, None),
+    };
+
+    let guard = LOADER_LOCK.lock();
+    let mut objects = Vec::new();
+    while let Some(shared_object) = loaded_objects::get(&guard, objects.len()) {
+        objects.push(shared_object);
+    }
+    drop(guard);
+
+    let mut referenced = false;
+    for &shared_object in &objects {
+        // We relocated ourselves long ago.
+        if core::ptr::eq(shared_object, interpreter) {
+            continue;
+        }
+
+        // The same reference usually shows up in several relocations, each kind is only explained once:
+        let mut explained = Vec::new();
+        for rela in shared_object.relocations.iter() {
+            let symbol_index = rela.r_sym() as usize;
+            if symbol_index == 0 || shared_object.symbol_name(symbol_index) != name {
+                continue;
+            }
+            let required = shared_object
+                .symbol_versions
+                .required(symbol_index, &shared_object.string_table);
+            let required_name = required.map(|required| required.name);
+            if version.is_some() && required_name != version {
+                continue;
+            }
+            let key = (required_name, rela.r_type() == R_X86_64_COPY);
+            if explained.contains(&key) {
+                continue;
+            }
+            explained.push(key);
+
+            referenced = true;
+            explain_reference(shared_object, rela, &objects);
+        }
+    }
+
+    if !referenced {
+        io::write(io::STD_OUT, "no loaded object references `");
+        io::write(io::STD_OUT, query);
+        io::write(io::STD_OUT, "'\n");
+    }
+    referenced
+}
+
+unsafe fn explain_reference(
+    shared_object: &'static SharedObject,
+    rela: &'static Rela,
+    objects: &[&'static SharedObject],
+) {
+    let Some(binding) = symbol_lookup::resolve(shared_object, rela) else {
+        return;
+    };
+    let is_copy = rela.r_type() == R_X86_64_COPY;
+
+    io::write(io::STD_OUT, "`");
+    io::write(io::STD_OUT, binding.name);
+    io::write(io::STD_OUT, "' referenced by ");
+    io::write(io::STD_OUT, shared_object.display_name());
+    io::write(io::STD_OUT, " (");
+    io::write(
+        io::STD_OUT,
+        relocation_name(rela.r_type()).unwrap_or("<unknown relocation>"),
+    );
+    if let Some(version) = binding.version {
+        io::write(io::STD_OUT, ", version ");
+        io::write(io::STD_OUT, version.name);
+        io::write(io::STD_OUT, " from ");
+        io::write(
+            io::STD_OUT,
+            version.file.unwrap_or(shared_object.display_name()),
+        );
+    }
+    io::write(io::STD_OUT, ")\n  lookup scope:\n");
+    for &object in objects {
+        io::write(io::STD_OUT, "    ");
+        io::write(io::STD_OUT, object.display_name());
+        if is_copy && core::ptr::eq(object, shared_object) {
+            io::write(io::STD_OUT, " (skipped, it holds the copy)");
+        }
+        io::write(io::STD_OUT, "\n");
+    }
+
+    // Every definition by that name, in scope order:
+    let mut candidates = Vec::new();
+    let mut bound = false;
+    for &object in objects {
+        let Some(hash_table) = object.hash_table.as_ref() else {
+            continue;
+        };
+        for symbol_index in hash_table.candidates(binding.name) {
+            let symbol = object.symbol_table.get(symbol_index);
+            if symbol.st_shndx == SHN_UNDEF || object.symbol_name(symbol_index) != binding.name {
+                continue;
+            }
+            let is_definition = binding.definition.is_some_and(|definition: Definition| {
+                core::ptr::eq(definition.shared_object, object)
+                    && definition.symbol_index == symbol_index
+            });
+            let verdict = if is_definition {
+                bound = true;
+                Verdict::Bound
+            } else if !symbol.is_exported_definition() {
+                Verdict::NotExported
+            } else if is_copy && core::ptr::eq(object, shared_object) {
+                Verdict::CopyItself
+            } else if !symbol_lookup::version_matches(
+                object,
+                symbol_index,
+                binding.version.as_ref(),
+            ) {
+                Verdict::WrongVersion
+            } else {
+                Verdict::Shadowed
+            };
+            candidates.push(Candidate {
+                shared_object: object,
+                symbol_index,
+                verdict,
+            });
+        }
+    }
+    // Local references bind to a symbol that isn't in the hash table:
+    if let Some(definition) = binding.definition.filter(|_| !bound) {
+        candidates.insert(
+            0,
+            Candidate {
+                shared_object: definition.shared_object,
+                symbol_index: definition.symbol_index,
+                verdict: Verdict::Bound,
+            },
+        );
+    }
+
+    io::write(io::STD_OUT, "  candidates:\n");
+    if candidates.is_empty() {
+        io::write(io::STD_OUT, "    none\n");
+    }
+    for candidate in &candidates {
+        print_candidate(candidate);
+    }
+
+    io::write(io::STD_OUT, "  rule: ");
+    io::write(
+        io::STD_OUT,
+        rule(shared_object, rela, &binding, &candidates),
+    );
+    io::write(io::STD_OUT, "\n\n");
+}
+
+/// Prints `object: NAME@@VERSION  BIND TYPE VISIBILITY  verdict`, like `readelf` shows the symbol.
+unsafe fn print_candidate(candidate: &Candidate) {
+    let object = candidate.shared_object;
+    let symbol = object.symbol_table.get(candidate.symbol_index);
+
+    io::write(io::STD_OUT, "    ");
+    io::write(io::STD_OUT, object.display_name());
+    io::write(io::STD_OUT, ": ");
+    io::write(io::STD_OUT, object.symbol_name(candidate.symbol_index));
+    let defined = object
+        .symbol_versions
+        .defined(candidate.symbol_index, &object.string_table);
+    if let Some((version, hidden)) =
+        defined.and_then(|defined| Some((defined.name?, defined.hidden)))
+    {
+        io::write(io::STD_OUT, if hidden { "@" } else { "@@" });
+        io::write(io::STD_OUT, version);
+    }
+    io::write(io::STD_OUT, "  ");
+    io::write(io::STD_OUT, symbol.bind_name());
+    io::write(io::STD_OUT, " ");
+    io::write(io::STD_OUT, symbol.type_name());
+    io::write(io::STD_OUT, " ");
+    io::write(io::STD_OUT, symbol.visibility_name());
+    io::write(
+        io::STD_OUT,
+        match candidate.verdict {
+            Verdict::Bound => "  bound here\n",
+            Verdict::NotExported => "  ignored: not exported\n",
+            Verdict::CopyItself => "  ignored: it's the copy itself\n",
+            Verdict::WrongVersion => "  ignored: the version doesn't match\n",
+            Verdict::Shadowed => "  shadowed: an earlier definition in the scope won\n",
+        },
+    );
+}
+
+/// Which rule picked the winner, in the order the lookup applies them.
+unsafe fn rule(
+    shared_object: &SharedObject,
+    rela: &Rela,
+    binding: &Binding,
+    candidates: &[Candidate],
+) -> &'static str {
+    let symbol = shared_object.symbol_table.get(rela.r_sym() as usize);
+    let Some(definition) = binding.definition else {
+        return if binding.weak {
+            "unresolved, nothing in the scope defines it; the reference is weak so it's left as null"
+        } else {
+            "unresolved, nothing in the scope defines it (undefined symbol)"
+        };
+    };
+
+    if symbol.st_bind() == STB_LOCAL {
+        return "local symbol, references always bind within the object";
+    }
+    if symbol_lookup::is_protected_definition(rela, &symbol) {
+        return "protected, the object's own definition can't be interposed";
+    }
+    if rela.r_type() == R_X86_64_COPY {
+        return "copy relocation, the executable's copy is skipped and the first definition after it in the scope wins";
+    }
+
+    let winner = candidates
+        .iter()
+        .position(|candidate| matches!(candidate.verdict, Verdict::Bound))
+        .unwrap_or_default();
+    if candidates[..winner]
+        .iter()
+        .any(|candidate| matches!(candidate.verdict, Verdict::WrongVersion))
+    {
+        return "version match, earlier definitions in the scope have the wrong version";
+    }
+    let weak_winner = definition.symbol.st_bind() == STB_WEAK;
+    if weak_winner
+        && candidates[winner..].iter().any(|candidate| {
+            matches!(candidate.verdict, Verdict::Shadowed)
+                && candidate
+                    .shared_object
+                    .symbol_table
+                    .get(candidate.symbol_index)
+                    .st_bind()
+                    != STB_WEAK
+        })
+    {
+        return "scope order, a weak definition is as good as a global one so the first in the scope wins (like glibc without LD_DYNAMIC_WEAK)";
+    }
+    "scope order, the first definition in the lookup scope wins"
+}
@@ -0,0 +1,12 @@
+use core::ffi::c_ulong;
+
+use crate::linux::auxiliary_vector;
+
+/// Looks up the `a_type` entry of the program's auxiliary vector, returning `0` if there isn't one; what `getauxval`
+/// does, for a libc to build it on.
+///
+/// NOTE: `getauxval` also sets `errno` to `ENOENT` for missing entries, that's left to the caller since it's theirs.
+#[cfg_attr(not(test), no_mangle)]
+pub extern "C" fn _dl_auxv(a_type: c_ulong) -> c_ulong {
+    auxiliary_vector::get().get(a_type as usize).unwrap_or(0) as c_ulong
+}
@@ -0,0 +1,38 @@
+use core::ffi::{c_int, c_void};
+
+use crate::mapped_ranges;
+
+/// The result of `_dl_find_object`, laid out exactly like glibc's `struct dl_find_object`.
+#[repr(C)]
+pub struct FindObjectResult {
+    pub dlfo_flags: u64,
+    pub dlfo_map_start: *mut c_void,
+    pub dlfo_map_end: *mut c_void,
+    pub dlfo_link_map: *mut c_void,
+    pub dlfo_eh_frame: *mut c_void,
+    pub __dlfo_reserved: [u64; 7],
+}
+
+/// Finds the object containing `address`, returning `0` on success and `-1` if nothing is mapped there.
+///
+/// Newer unwinders (libgcc >= 12) use this instead of scanning every object with `dl_iterate_phdr`.
+/// It never takes the `LOADER_LOCK`, see `mapped_ranges` for how that works.
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn _dl_find_object(
+    address: *mut c_void,
+    result: *mut FindObjectResult,
+) -> c_int {
+    let Some(range) = mapped_ranges::find(address.addr()) else {
+        return -1;
+    };
+
+    *result = FindObjectResult {
+        dlfo_flags: 0,
+        dlfo_map_start: range.start as *mut c_void,
+        dlfo_map_end: range.end as *mut c_void,
+        dlfo_link_map: (*range.shared_object).link_map.get().cast(),
+        dlfo_eh_frame: range.eh_frame_header.cast_mut().cast(),
+        __dlfo_reserved: [0; 7],
+    };
+    0
+}
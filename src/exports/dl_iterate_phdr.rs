@@ -0,0 +1,90 @@
+use core::{
+    ffi::{c_char, c_int, c_void},
+    ptr::null_mut,
+};
+
+use crate::{
+    arch::thread_pointer::get_thread_pointer,
+    elf::{
+        program_header::ProgramHeader,
+        thread_local_storage::{DynamicThreadVectorItem, ThreadControlBlock},
+    },
+    loaded_objects,
+    loader_lock::LOADER_LOCK,
+};
+
+/// The information passed to each `dl_iterate_phdr` callback, laid out exactly like glibc's `struct dl_phdr_info`.
+#[repr(C)]
+pub struct ProgramHeaderInfo {
+    pub dlpi_addr: usize,
+    pub dlpi_name: *const c_char,
+    pub dlpi_phdr: *const ProgramHeader,
+    pub dlpi_phnum: u16,
+    // Everything after here is an extension; callers check the `size` argument before reading it.
+    pub dlpi_adds: u64,
+    pub dlpi_subs: u64,
+    pub dlpi_tls_modid: usize,
+    pub dlpi_tls_data: *mut c_void,
+}
+
+pub type ProgramHeaderCallback =
+    unsafe extern "C" fn(info: *mut ProgramHeaderInfo, size: usize, data: *mut c_void) -> c_int;
+
+/// Calls `callback` once for every loaded object until it returns something other than zero.
+///
+/// This is how unwinders (Rust panics, C++ exceptions) and profilers find the `.eh_frame_hdr` of each object.
+/// The `LOADER_LOCK` is held the whole time, so the set of objects can't change mid-iteration, except by the callback itself.
+#[cfg_attr(not(test), no_mangle)]
+pub unsafe extern "C" fn dl_iterate_phdr(
+    callback: Option<ProgramHeaderCallback>,
+    data: *mut c_void,
+) -> c_int {
+    let Some(callback) = callback else {
+        return 0;
+    };
+    let _guard = LOADER_LOCK.lock();
+
+    let mut index = 0;
+    // NOTE: The list is re-checked every iteration because the callback is allowed to load more objects.
+    while let Some(shared_object) = loaded_objects::get(&_guard, index) {
+        let mut info = ProgramHeaderInfo {
+            dlpi_addr: shared_object.base.addr(),
+            dlpi_name: shared_object.name.as_ptr(),
+            dlpi_phdr: shared_object.program_header_table.as_ptr(),
+            dlpi_phnum: shared_object.program_header_table.len() as u16,
+            dlpi_adds: loaded_objects::added(),
+            dlpi_subs: loaded_objects::removed(),
+            dlpi_tls_modid: shared_object.thread_local_block.unwrap_or(0),
+            dlpi_tls_data: shared_object
+                .thread_local_block
+                .map_or(null_mut(), thread_local_data),
+        };
+
+        let result = callback(&mut info, size_of::<ProgramHeaderInfo>(), data);
+        if result != 0 {
+            return result;
+        }
+        index += 1;
+    }
+    0
+}
+
+/// Looks up the current thread's block for a TLS module, null if it hasn't been allocated (yet).
+fn thread_local_data(module_id: usize) -> *mut c_void {
+    unsafe {
+        let thread_control_block = get_thread_pointer() as *const ThreadControlBlock;
+        // The first entry in the DTV is the generation counter, modules are indexed from one:
+        let dynamic_thread_vector =
+            (*thread_control_block).dynamic_thread_vector as *const DynamicThreadVectorItem;
+        if dynamic_thread_vector.is_null() {
+            return null_mut();
+        }
+        // A module loaded after the vector was last grown doesn't have an entry (yet):
+        let length = (*dynamic_thread_vector.sub(1)).length;
+        if module_id == 0 || module_id > length {
+            return null_mut();
+        }
+
+        (*dynamic_thread_vector.add(module_id)).pointer
+    }
+}
@@ -0,0 +1,4 @@
+pub mod dl_auxv;
+pub mod dl_iterate_phdr;
+pub mod dl_find_object;
+pub mod r_debug;
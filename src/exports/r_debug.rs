@@ -0,0 +1,143 @@
+use core::{
+    arch::asm,
+    ffi::{c_char, c_int},
+    ptr::{addr_of_mut, null_mut},
+};
+
+use crate::{
+    elf::dynamic_array::{DynamicArrayItem, DT_DEBUG, DT_NULL},
+    shared_object::SharedObject,
+};
+
+/// A node in the list of loaded objects debuggers walk, laid out exactly like the public part of glibc's `struct link_map`.
+#[repr(C)]
+pub struct LinkMap {
+    /// The difference between the addresses in the ELF file and the addresses in memory.
+    pub l_addr: usize,
+    pub l_name: *const c_char,
+    /// The object's dynamic array.
+    pub l_ld: *mut DynamicArrayItem,
+    pub l_next: *mut LinkMap,
+    pub l_prev: *mut LinkMap,
+}
+
+// The values of `r_state`:
+pub const RT_CONSISTENT: c_int = 0;
+pub const RT_ADD: c_int = 1;
+pub const RT_DELETE: c_int = 2;
+
+/// The rendezvous structure debuggers find through `DT_DEBUG` (or our `_r_debug` symbol), laid out exactly like glibc's `struct r_debug`.
+#[repr(C)]
+pub struct DebugRendezvous {
+    pub r_version: c_int,
+    pub r_map: *mut LinkMap,
+    /// The address debuggers put a breakpoint on to hear about changes to `r_map`.
+    pub r_brk: usize,
+    pub r_state: c_int,
+    /// Our own base address.
+    pub r_ldbase: usize,
+}
+
+/// glibc's `struct r_debug_extended`, `r_version` 2 promises debuggers that `r_next` is there.
+#[repr(C)]
+pub struct ExtendedDebugRendezvous {
+    pub base: DebugRendezvous,
+    /// The rendezvous structure for the next link map namespace.
+    pub r_next: *mut ExtendedDebugRendezvous,
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub static mut _r_debug: ExtendedDebugRendezvous = ExtendedDebugRendezvous {
+    base: DebugRendezvous {
+        r_version: 2,
+        r_map: null_mut(),
+        r_brk: 0,
+        r_state: RT_CONSISTENT,
+        r_ldbase: 0,
+    },
+    r_next: null_mut(),
+};
+
+/// Does nothing; debuggers put a breakpoint here and check `r_state` when it's hit.
+///
+/// It's called once right before the link map changes, and once more when it's `RT_CONSISTENT` again.
+#[cfg_attr(not(test), no_mangle)]
+#[inline(never)]
+pub extern "C" fn _dl_debug_state() {
+    // Without this the compiler would notice there is nothing to call and remove the calls. :/
+    unsafe { asm!("", options(nostack, preserves_flags)) };
+}
+
+/// Fills in the parts of `_r_debug` that are only known at runtime.
+pub unsafe fn initialize(miros_base: *const ()) {
+    let rendezvous = addr_of_mut!(_r_debug.base);
+    (*rendezvous).r_brk = _dl_debug_state as *const () as usize;
+    (*rendezvous).r_ldbase = miros_base.addr();
+}
+
+/// Points the executable's `DT_DEBUG` entry (if it has one) at `_r_debug`, that's where debuggers look first.
+pub unsafe fn fill_debug_entry(executable: &SharedObject) {
+    let mut item = (*executable.link_map.get()).l_ld;
+    while (*item).d_tag != DT_NULL {
+        if (*item).d_tag == DT_DEBUG {
+            (*item).d_un.d_ptr = addr_of_mut!(_r_debug).cast();
+        }
+        item = item.add(1);
+    }
+}
+
+/// Adds an object to the end of the link map; the caller must hold the `LOADER_LOCK`.
+pub unsafe fn link(shared_object: &'static SharedObject) {
+    let rendezvous = addr_of_mut!(_r_debug.base);
+    (*rendezvous).r_state = RT_ADD;
+    _dl_debug_state();
+
+    let link_map = shared_object.link_map.get();
+    let mut last = (*rendezvous).r_map;
+    if last.is_null() {
+        (*rendezvous).r_map = link_map;
+    } else {
+        while !(*last).l_next.is_null() {
+            last = (*last).l_next;
+        }
+        (*link_map).l_prev = last;
+        (*last).l_next = link_map;
+    }
+
+    (*rendezvous).r_state = RT_CONSISTENT;
+    _dl_debug_state();
+}
+
+/// Removes an object from the link map; the caller must hold the `LOADER_LOCK`.
+pub unsafe fn unlink(shared_object: &'static SharedObject) {
+    let rendezvous = addr_of_mut!(_r_debug.base);
+    (*rendezvous).r_state = RT_DELETE;
+    _dl_debug_state();
+
+    let link_map = shared_object.link_map.get();
+    if (*link_map).l_prev.is_null() {
+        (*rendezvous).r_map = (*link_map).l_next;
+    } else {
+        (*(*link_map).l_prev).l_next = (*link_map).l_next;
+    }
+    if !(*link_map).l_next.is_null() {
+        (*(*link_map).l_next).l_prev = (*link_map).l_prev;
+    }
+    (*link_map).l_next = null_mut();
+    (*link_map).l_prev = null_mut();
+
+    (*rendezvous).r_state = RT_CONSISTENT;
+    _dl_debug_state();
+}
+
+impl LinkMap {
+    pub fn new(base: *const (), name: *const c_char, dynamic: *mut DynamicArrayItem) -> Self {
+        Self {
+            l_addr: base.addr(),
+            l_name: name,
+            l_ld: dynamic,
+            l_next: null_mut(),
+            l_prev: null_mut(),
+        }
+    }
+}
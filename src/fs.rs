@@ -0,0 +1,118 @@
+use alloc::{ffi::CString, string::String, vec::Vec};
+use core::ffi::CStr;
+
+use crate::arch::syscall::{self, Errno, Stat, AT_FDCWD, EINTR, EINVAL, EIO, O_CLOEXEC, O_RDONLY};
+
+/// An open file, closed when dropped; just enough of `std::fs::File` for what we read and write.
+pub struct File {
+    fd: i32,
+}
+
+impl File {
+    /// Opens `path` for reading.
+    pub fn open(path: &str) -> Result<Self, Errno> {
+        Self::open_with(path, O_RDONLY, 0)
+    }
+
+    /// Opens `path` for reading, as the bytes it is; for paths that don't have to be UTF-8, like the ones we are given.
+    pub fn open_c_str(path: &CStr) -> Result<Self, Errno> {
+        let fd = syscall::openat(AT_FDCWD, path, O_RDONLY | O_CLOEXEC, 0)?;
+        Ok(Self { fd })
+    }
+
+    /// Opens `path` with the `O_*` `flags` (it's always `O_CLOEXEC`), and `mode` for a file it creates.
+    pub fn open_with(path: &str, flags: i32, mode: u32) -> Result<Self, Errno> {
+        // Paths with a null byte in them can't exist:
+        let path = CString::new(path).map_err(|_| EINVAL)?;
+        let fd = syscall::openat(AT_FDCWD, &path, flags | O_CLOEXEC, mode)?;
+        Ok(Self { fd })
+    }
+
+    pub fn as_raw_fd(&self) -> i32 {
+        self.fd
+    }
+
+    /// Gives up the file descriptor, it stays open.
+    pub fn into_raw_fd(self) -> i32 {
+        let fd = self.fd;
+        core::mem::forget(self);
+        fd
+    }
+
+    pub fn metadata(&self) -> Result<Stat, Errno> {
+        syscall::fstat(self.fd)
+    }
+
+    /// Fills `buffer` from `offset` in the file, failing with `EIO` if the file ends first.
+    pub fn read_exact_at(&self, mut buffer: &mut [u8], mut offset: u64) -> Result<(), Errno> {
+        while !buffer.is_empty() {
+            match syscall::pread64(self.fd, buffer, offset) {
+                Ok(0) => return Err(EIO),
+                Ok(read) => {
+                    buffer = &mut buffer[read..];
+                    offset += read as u64;
+                }
+                Err(EINTR) => continue,
+                Err(errno) => return Err(errno),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads everything from where the file is at to its end.
+    pub fn read_to_end(&self, contents: &mut Vec<u8>) -> Result<(), Errno> {
+        let mut buffer = [0; 4096];
+        loop {
+            match syscall::read(self.fd, &mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => contents.extend_from_slice(&buffer[..read]),
+                Err(EINTR) => continue,
+                Err(errno) => return Err(errno),
+            }
+        }
+    }
+
+    pub fn write_all(&self, mut bytes: &[u8]) -> Result<(), Errno> {
+        while !bytes.is_empty() {
+            match syscall::write(self.fd, bytes) {
+                Ok(0) => return Err(EIO),
+                Ok(written) => bytes = &bytes[written..],
+                Err(EINTR) => continue,
+                Err(errno) => return Err(errno),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        let _ = unsafe { syscall::close(self.fd) };
+    }
+}
+
+/// The whole file at `path`.
+pub fn read(path: &str) -> Result<Vec<u8>, Errno> {
+    let mut contents = Vec::new();
+    File::open(path)?.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+/// The whole file at `path`, which has to be UTF-8.
+pub fn read_to_string(path: &str) -> Result<String, Errno> {
+    String::from_utf8(read(path)?).map_err(|_| EINVAL)
+}
+
+/// Where the symbolic link at `path` points.
+pub fn read_link(path: &str) -> Result<String, Errno> {
+    let path = CString::new(path).map_err(|_| EINVAL)?;
+    let mut buffer = [0; 4096];
+    let length = syscall::readlink(&path, &mut buffer)?;
+    String::from_utf8(buffer[..length].to_vec()).map_err(|_| EINVAL)
+}
+
+/// `stat`, following symbolic links.
+pub fn metadata(path: &str) -> Result<Stat, Errno> {
+    let path = CString::new(path).map_err(|_| EINVAL)?;
+    syscall::fstatat(AT_FDCWD, &path, 0)
+}
@@ -0,0 +1,402 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write as _;
+
+use crate::{
+    arch::io,
+    dependencies::{Dependency, Target},
+    json, loaded_objects,
+    loader_lock::LOADER_LOCK,
+    shared_object::SharedObject,
+};
+
+/// What `--graph=FORMAT` writes.
+#[derive(Clone, Copy)]
+pub enum Format {
+    /// Graphviz, `miros --graph=dot ./program | dot -Tsvg > graph.svg`.
+    Dot,
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        match name {
+            b"dot" => Some(Self::Dot),
+            b"json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A node, either a loaded object or a library that couldn't be found.
+enum Node {
+    Object(&'static SharedObject),
+    Missing(&'static str),
+}
+
+/// The dependency graph: every loaded object, plus a node for each library that couldn't be found.
+///
+/// Nodes are numbered in load order, the missing ones come after.
+struct Graph<'a> {
+    nodes: Vec<Node>,
+    dependencies: &'a [Dependency],
+    interpreter: &'static SharedObject,
+}
+
+impl<'a> Graph<'a> {
+    /// The graph of `objects` (in load order) and the `dependencies` between them.
+    fn new(
+        objects: Vec<&'static SharedObject>,
+        dependencies: &'a [Dependency],
+        interpreter: &'static SharedObject,
+    ) -> Self {
+        let mut nodes: Vec<Node> = objects.into_iter().map(Node::Object).collect();
+        for dependency in dependencies {
+            if let Target::NotFound = dependency.target {
+                if !nodes
+                    .iter()
+                    .any(|node| matches!(node, Node::Missing(name) if *name == dependency.name))
+                {
+                    nodes.push(Node::Missing(dependency.name));
+                }
+            }
+        }
+
+        Self {
+            nodes,
+            dependencies,
+            interpreter,
+        }
+    }
+
+    /// NOTE: Everything that requests or satisfies a dependency was loaded, so it has a node. Not finding one is a bug,
+    /// and pointing the edge at some other node instead would quietly draw the wrong graph.
+    fn node_of(&self, shared_object: &SharedObject) -> usize {
+        self.nodes
+            .iter()
+            .position(|node| matches!(node, Node::Object(object) if core::ptr::eq(*object, shared_object)))
+            .expect("every object in a dependency was loaded, so it has a node")
+    }
+
+    /// NOTE: `new` adds a node for every library that wasn't found, see `node_of`.
+    fn missing_node(&self, name: &str) -> usize {
+        self.nodes
+            .iter()
+            .position(|node| matches!(node, Node::Missing(missing) if *missing == name))
+            .expect("every library that wasn't found has a node")
+    }
+
+    /// The node an edge points to, and whether an already loaded object satisfied it.
+    fn edge(&self, dependency: &Dependency) -> (usize, bool) {
+        match dependency.target {
+            Target::Loaded(target) => (self.node_of(target), false),
+            Target::AlreadyLoaded(target) => (self.node_of(target), true),
+            Target::Interpreter => (self.node_of(self.interpreter), true),
+            Target::NotFound => (self.missing_node(dependency.name), false),
+        }
+    }
+}
+
+/// Writes the `DT_NEEDED` graph of everything that was loaded: one node per object and one edge per `DT_NEEDED` entry.
+///
+/// Edges satisfied by an object that was already loaded (rather than loaded for them) are marked, and so are libraries
+/// that couldn't be found. The executable goes by the path we were given, `--graph` is only there when we are run
+/// directly.
+pub unsafe fn print(
+    format: Format,
+    dependencies: &[Dependency],
+    interpreter: &'static SharedObject,
+) {
+    let mut objects = Vec::new();
+    let guard = LOADER_LOCK.lock();
+    while let Some(shared_object) = loaded_objects::get(&guard, objects.len()) {
+        objects.push(shared_object);
+    }
+    drop(guard);
+
+    let graph = Graph::new(objects, dependencies, interpreter);
+    let output = match format {
+        Format::Dot => to_dot(&graph),
+        Format::Json => to_json(&graph),
+    };
+    io::write(io::STD_OUT, &output);
+}
+
+/// ```text
+/// digraph dependencies {
+///   node [shape=box];
+///   0 [label="./program\nbuild-id: be40..."];
+///   1 [label="/lib/x86_64-linux-gnu/libc.so.6\nsoname: libc.so.6\nbuild-id: 93ac..."];
+///   2 [label="libgone.so\nnot found", color=red, fontcolor=red];
+///   0 -> 1 [label="libc.so.6"];
+///   0 -> 2 [label="libgone.so", color=red];
+///   3 -> 1 [label="libc.so.6", style=dashed];
+/// }
+/// ```
+unsafe fn to_dot(graph: &Graph) -> String {
+    let mut output = String::from("digraph dependencies {\n  node [shape=box];\n");
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let mut label = String::new();
+        let attributes = match node {
+            Node::Object(shared_object) => {
+                label.push_str(shared_object.display_name());
+                if let Some(soname) = shared_object.soname() {
+                    label.push_str("\nsoname: ");
+                    label.push_str(soname);
+                }
+                if let Some(build_id) = shared_object.build_id_hex() {
+                    label.push_str("\nbuild-id: ");
+                    label.push_str(&build_id);
+                }
+                ""
+            }
+            Node::Missing(name) => {
+                label.push_str(name);
+                label.push_str("\nnot found");
+                ", color=red, fontcolor=red"
+            }
+        };
+        let _ = writeln!(output, "  {index} [label={}{attributes}];", quote(&label));
+    }
+    for dependency in graph.dependencies {
+        let (target, already_loaded) = graph.edge(dependency);
+        let attributes = match dependency.target {
+            Target::NotFound => ", color=red",
+            _ if already_loaded => ", style=dashed",
+            _ => "",
+        };
+        let _ = writeln!(
+            output,
+            "  {} -> {target} [label={}{attributes}];",
+            graph.node_of(dependency.requester),
+            quote(dependency.name),
+        );
+    }
+    output.push_str("}\n");
+    output
+}
+
+/// A DOT string; `\n` in it becomes a line break in the label.
+fn quote(string: &str) -> String {
+    let mut quoted = String::from("\"");
+    for character in string.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// ```text
+/// {"nodes":[
+/// {"id":0,"path":"./program","soname":null,"build_id":"be40...","found":true},
+/// {"id":2,"path":null,"soname":"libgone.so","build_id":null,"found":false}
+/// ],"edges":[
+/// {"from":0,"to":1,"name":"libc.so.6","already_loaded":false},
+/// ...
+/// ]}
+/// ```
+///
+/// NOTE: A library that wasn't found has no path, the name it was needed by goes in `soname`.
+unsafe fn to_json(graph: &Graph) -> String {
+    let mut output = String::from("{\"nodes\":[");
+    for (index, node) in graph.nodes.iter().enumerate() {
+        output.push_str(if index == 0 { "\n" } else { ",\n" });
+        let _ = write!(output, "{{\"id\":{index},\"path\":");
+        let (soname, build_id) = match node {
+            Node::Object(shared_object) => {
+                json::push_string(&mut output, shared_object.display_name());
+                (shared_object.soname(), shared_object.build_id_hex())
+            }
+            Node::Missing(name) => {
+                output.push_str("null");
+                (Some(*name), None)
+            }
+        };
+        output.push_str(",\"soname\":");
+        json::push_optional_string(&mut output, soname);
+        output.push_str(",\"build_id\":");
+        json::push_optional_string(&mut output, build_id.as_deref());
+        let _ = write!(output, ",\"found\":{}}}", matches!(node, Node::Object(_)));
+    }
+
+    output.push_str("\n],\"edges\":[");
+    for (index, dependency) in graph.dependencies.iter().enumerate() {
+        output.push_str(if index == 0 { "\n" } else { ",\n" });
+        let (target, already_loaded) = graph.edge(dependency);
+        let _ = write!(
+            output,
+            "{{\"from\":{},\"to\":{target},\"name\":",
+            graph.node_of(dependency.requester)
+        );
+        json::push_string(&mut output, dependency.name);
+        let _ = write!(output, ",\"already_loaded\":{already_loaded}}}");
+    }
+    output.push_str("\n]}\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+    use core::{ffi::CStr, mem::offset_of};
+
+    use crate::elf::{
+        dynamic_array::{DynamicArrayItem, DynamicArrayUnion, DT_NULL, DT_SONAME, DT_STRTAB},
+        header::{ElfHeader, ET_DYN},
+        program_header::{ProgramHeader, PT_DYNAMIC, PT_LOAD, PT_NOTE},
+    };
+
+    /// The smallest image that makes a node: a `PT_DYNAMIC` with just the string table and (maybe) a soname, and a
+    /// `PT_NOTE` with a build ID. Addresses are offsets into it, as if it were mapped at its own address.
+    #[repr(C)]
+    struct Image {
+        header: ElfHeader,
+        program_header_table: [ProgramHeader; 3],
+        dynamic_array: [DynamicArrayItem; 3],
+        /// The build ID note: name size, descriptor size, `NT_GNU_BUILD_ID`, then the name and descriptor.
+        note: [u32; 3],
+        note_name: [u8; 4],
+        build_id: [u8; 4],
+        /// The soname, at index 1.
+        string_table: [u8; 32],
+    }
+
+    fn item(d_tag: usize, d_val: usize) -> DynamicArrayItem {
+        DynamicArrayItem {
+            d_tag,
+            d_un: DynamicArrayUnion { d_val },
+        }
+    }
+
+    /// An object called `path`, it lives as long as the test does.
+    fn object(
+        path: &'static CStr,
+        soname: Option<&str>,
+        build_id: [u8; 4],
+    ) -> &'static SharedObject {
+        let mut string_table = [0; 32];
+        if let Some(soname) = soname {
+            string_table[1..=soname.len()].copy_from_slice(soname.as_bytes());
+        }
+        let image = Box::leak(Box::new(Image {
+            header: ElfHeader {
+                e_type: ET_DYN,
+                e_phoff: offset_of!(Image, program_header_table),
+                e_phentsize: size_of::<ProgramHeader>() as u16,
+                e_phnum: 3,
+                ..ElfHeader::default()
+            },
+            program_header_table: [
+                ProgramHeader {
+                    p_type: PT_LOAD,
+                    p_memsz: size_of::<Image>(),
+                    ..ProgramHeader::default()
+                },
+                ProgramHeader {
+                    p_type: PT_DYNAMIC,
+                    p_vaddr: offset_of!(Image, dynamic_array),
+                    ..ProgramHeader::default()
+                },
+                ProgramHeader {
+                    p_type: PT_NOTE,
+                    p_vaddr: offset_of!(Image, note),
+                    p_memsz: offset_of!(Image, string_table) - offset_of!(Image, note),
+                    ..ProgramHeader::default()
+                },
+            ],
+            dynamic_array: [
+                item(DT_STRTAB, offset_of!(Image, string_table)),
+                match soname {
+                    Some(_) => item(DT_SONAME, 1),
+                    None => item(DT_NULL, 0),
+                },
+                item(DT_NULL, 0),
+            ],
+            note: [4, 4, 3],
+            note_name: *b"GNU\0",
+            build_id,
+            string_table,
+        }));
+
+        // NOTE: Like the vDSO, the image is already in memory; only the name needs to be its own.
+        let mut shared_object = unsafe { SharedObject::from_vdso(&image.header) };
+        shared_object.name = path;
+        Box::leak(Box::new(shared_object))
+    }
+
+    fn dependency(
+        requester: &'static SharedObject,
+        name: &'static str,
+        target: Target,
+    ) -> Dependency {
+        Dependency {
+            requester,
+            name,
+            target,
+        }
+    }
+
+    #[test]
+    fn missing_libraries_get_a_node_of_their_own() {
+        let (executable, libc, interpreter) = (
+            object(c"./program", None, [0xe0; 4]),
+            object(c"/lib/libc.so.6", Some("libc.so.6"), [0x1c; 4]),
+            object(c"/lib/ld.so", Some("ld.so"), [0x1d; 4]),
+        );
+        let dependencies = [
+            dependency(executable, "libc.so.6", Target::Loaded(libc)),
+            dependency(executable, "libgone.so", Target::NotFound),
+            dependency(libc, "ld.so", Target::Interpreter),
+            dependency(libc, "libgone.so", Target::NotFound),
+        ];
+        let graph = Graph::new(
+            vec![executable, libc, interpreter],
+            &dependencies,
+            interpreter,
+        );
+
+        // The missing library comes after every loaded object, once no matter how many need it:
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.edge(&dependencies[0]), (1, false));
+        assert_eq!(graph.edge(&dependencies[1]), (3, false));
+        assert_eq!(graph.edge(&dependencies[2]), (2, true));
+        assert_eq!(graph.edge(&dependencies[3]), (3, false));
+
+        let dot = unsafe { to_dot(&graph) };
+        assert!(dot.contains("  0 [label=\"./program\\nbuild-id: e0e0e0e0\"];\n"));
+        assert!(dot.contains(
+            "  1 [label=\"/lib/libc.so.6\\nsoname: libc.so.6\\nbuild-id: 1c1c1c1c\"];\n"
+        ));
+        assert!(dot.contains("  3 [label=\"libgone.so\\nnot found\", color=red, fontcolor=red];\n"));
+        assert!(dot.contains("  0 -> 3 [label=\"libgone.so\", color=red];\n"));
+        assert!(dot.contains("  1 -> 2 [label=\"ld.so\", style=dashed];\n"));
+
+        let json = unsafe { to_json(&graph) };
+        assert!(json.contains(
+            "{\"id\":2,\"path\":\"/lib/ld.so\",\"soname\":\"ld.so\",\"build_id\":\"1d1d1d1d\",\"found\":true}"
+        ));
+        assert!(json.contains(
+            "{\"id\":3,\"path\":null,\"soname\":\"libgone.so\",\"build_id\":null,\"found\":false}"
+        ));
+        assert!(
+            json.contains("{\"from\":1,\"to\":3,\"name\":\"libgone.so\",\"already_loaded\":false}")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "every object in a dependency was loaded")]
+    fn an_object_without_a_node_is_a_bug() {
+        let (executable, stray) = (
+            object(c"./program", None, [0xe0; 4]),
+            object(c"/lib/stray.so", Some("stray.so"), [0x5a; 4]),
+        );
+        let dependencies = [dependency(executable, "stray.so", Target::Loaded(stray))];
+        let graph = Graph::new(vec![executable], &dependencies, executable);
+        graph.edge(&dependencies[0]);
+    }
+}
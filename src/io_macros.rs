@@ -16,16 +16,27 @@ macro_rules! underline {
 pub(crate) use underline;
 
 // Printing
-macro_rules! syscall_print {
-    ($($message:expr),+ $(,)?) => {
+/// Writes strings, integers (`Hex(...)` for hexadecimal) and pointers to a file descriptor, without allocating or
+/// `core::fmt`, so it also works before we are relocated.
+macro_rules! syscall_write {
+    ($fd:expr, $($argument:expr),+ $(,)?) => {
         {
+            let mut writer = $crate::syscall_format::Writer::new($fd);
             $(
-                $crate::arch::write(1, $message);
+                $crate::syscall_format::SyscallDisplay::write_to(&$argument, &mut writer);
             )+
         }
     };
 }
 
+pub(crate) use syscall_write;
+
+macro_rules! syscall_print {
+    ($($argument:expr),+ $(,)?) => {
+        $crate::io_macros::syscall_write!(1, $($argument),+)
+    };
+}
+
 pub(crate) use syscall_print;
 
 macro_rules! syscall_debug_print {
@@ -40,13 +51,8 @@ macro_rules! syscall_debug_print {
 pub(crate) use syscall_debug_print;
 
 macro_rules! syscall_println {
-    ($($message:expr),+ $(,)?) => {
-        {
-            $(
-                $crate::arch::write(1, $message);
-            )+
-            $crate::arch::write(1, "\n");
-        }
+    ($($argument:expr),+ $(,)?) => {
+        $crate::io_macros::syscall_write!(1, $($argument),+, "\n")
     };
 }
 
@@ -0,0 +1,71 @@
+use alloc::string::String;
+use core::fmt::Write as _;
+
+/// Appends `string` as a JSON string; paths can contain anything but null bytes, so they need escaping.
+pub fn push_string(json: &mut String, string: &str) {
+    json.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            character if (character as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", character as u32);
+            }
+            character => json.push(character),
+        }
+    }
+    json.push('"');
+}
+
+/// Appends `string` as a JSON string, or `null`.
+pub fn push_optional_string(json: &mut String, string: Option<&str>) {
+    match string {
+        Some(string) => push_string(json, string),
+        None => json.push_str("null"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(string: &str) -> String {
+        let mut json = String::new();
+        push_string(&mut json, string);
+        json
+    }
+
+    #[test]
+    fn plain_strings_are_only_quoted() {
+        assert_eq!(string("/lib/libc.so.6"), "\"/lib/libc.so.6\"");
+        assert_eq!(string(""), "\"\"");
+        // JSON is UTF-8 too, so anything else is fine as it is:
+        assert_eq!(
+            string("/opt/bibliothèque/λ.so"),
+            "\"/opt/bibliothèque/λ.so\""
+        );
+    }
+
+    #[test]
+    fn quotes_and_backslashes_are_escaped() {
+        assert_eq!(string("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(string("C:\\lib"), "\"C:\\\\lib\"");
+    }
+
+    #[test]
+    fn control_characters_are_escaped() {
+        assert_eq!(string("a\nb\tc"), "\"a\\u000ab\\u0009c\"");
+        assert_eq!(string("\u{1}\u{1f}"), "\"\\u0001\\u001f\"");
+        // DEL isn't a control character as far as JSON is concerned:
+        assert_eq!(string("\u{7f}"), "\"\u{7f}\"");
+    }
+
+    #[test]
+    fn missing_strings_are_null() {
+        let mut json = String::new();
+        push_optional_string(&mut json, None);
+        json.push(',');
+        push_optional_string(&mut json, Some("libz.so.1"));
+        assert_eq!(json, "null,\"libz.so.1\"");
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::vec::Vec;
+
+use crate::fs;
+
+/// Where `ldconfig` writes the cache.
+const CACHE_FILE: &str = "/etc/ld.so.cache";
+
+/// The old (libc5) format, which may still come first in the file for compatibility.
+const OLD_MAGIC: &[u8] = b"ld.so-1.7.0";
+/// The new format, including its version.
+const NEW_MAGIC: &[u8] = b"glibc-ld.so.cache1.1";
+
+/// `flags` of an entry for a 64-bit x86 glibc library (`FLAG_ELF_LIBC6 | FLAG_X8664_LIB64`).
+#[cfg(target_arch = "x86_64")]
+const FLAG_NATIVE: i32 = 0x0303;
+/// The type and architecture bits of `flags`.
+const FLAG_MASK: i32 = 0xffff;
+
+/// `struct cache_file` from glibc, the old format's header.
+#[repr(C)]
+struct OldCacheHeader {
+    magic: [u8; 11],
+    nlibs: u32,
+}
+
+/// `struct file_entry` from glibc, an entry in the old format.
+#[repr(C)]
+struct OldCacheEntry {
+    flags: i32,
+    key: u32,
+    value: u32,
+}
+
+/// `struct cache_file_new` from glibc.
+#[repr(C)]
+struct CacheHeader {
+    magic: [u8; 20],
+    nlibs: u32,
+    len_strings: u32,
+    flags: u8,
+    padding_unused: [u8; 3],
+    extension_offset: u32,
+    unused: [u32; 3],
+}
+
+/// `struct file_entry_new` from glibc, `key` and `value` are offsets of the soname and path from the start of the header.
+#[repr(C)]
+struct CacheEntry {
+    flags: i32,
+    key: u32,
+    value: u32,
+    osversion_unused: u32,
+    hwcap: u64,
+}
+
+/// The library cache `ldconfig` builds, mapping sonames to paths so we don't have to search every system directory.
+///
+/// ```no_run
+/// |------------------------------|
+/// | OldCacheHeader (optional)    |
+/// | OldCacheEntry...             |
+/// |------------------------------| <- aligned like `CacheHeader`
+/// | CacheHeader                  |
+/// | CacheEntry...                |
+/// | strings...                   |
+/// |------------------------------|
+/// ```
+pub struct LibraryCache {
+    contents: Vec<u8>,
+    /// Where the new format header starts in `contents`.
+    header_offset: usize,
+}
+
+impl LibraryCache {
+    /// Reads the cache, returning `None` if there isn't one (or it's in a format we don't understand).
+    pub fn load() -> Option<Self> {
+        let contents = fs::read(CACHE_FILE).ok()?;
+
+        let mut header_offset = 0;
+        if contents.starts_with(OLD_MAGIC) {
+            let old_header = read::<OldCacheHeader>(&contents, 0)?;
+            let old_end = size_of::<OldCacheHeader>()
+                + old_header.nlibs as usize * size_of::<OldCacheEntry>();
+            header_offset = old_end.next_multiple_of(align_of::<CacheHeader>());
+        }
+
+        contents
+            .get(header_offset..)?
+            .starts_with(NEW_MAGIC)
+            .then_some(Self {
+                contents,
+                header_offset,
+            })
+    }
+
+    /// Looks up the path of the library `name`.
+    pub fn find(&self, name: &str) -> Option<&str> {
+        let header = read::<CacheHeader>(&self.contents, self.header_offset)?;
+        let entries_offset = self.header_offset + size_of::<CacheHeader>();
+
+        for index in 0..header.nlibs as usize {
+            let entry = read::<CacheEntry>(
+                &self.contents,
+                entries_offset + index * size_of::<CacheEntry>(),
+            )?;
+
+            // NOTE: Entries with `hwcap` set live in `glibc-hwcaps` subdirectories, and may need CPU features we don't check for.
+            if entry.flags & FLAG_MASK != FLAG_NATIVE || entry.hwcap != 0 {
+                continue;
+            }
+            if self.string(entry.key) == Some(name) {
+                return self.string(entry.value);
+            }
+        }
+        None
+    }
+
+    /// The null terminated string at `offset` from the header.
+    fn string(&self, offset: u32) -> Option<&str> {
+        let start = self.header_offset + offset as usize;
+        let bytes = self.contents.get(start..)?;
+        let length = bytes.iter().position(|&byte| byte == 0)?;
+        core::str::from_utf8(&bytes[..length]).ok()
+    }
+}
+
+/// Reads a `T` at `offset`, if it fits.
+fn read<T>(contents: &[u8], offset: usize) -> Option<T> {
+    let bytes = contents.get(offset..offset.checked_add(size_of::<T>())?)?;
+    Some(unsafe { core::ptr::read_unaligned(bytes.as_ptr() as *const T) })
+}
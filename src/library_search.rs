@@ -0,0 +1,380 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    arch::syscall::{Errno, EIO, ENOENT, ENOEXEC, ENOTDIR},
+    cli::Options,
+    debug::{debug_println, DEBUG_LIBS},
+    elf::header::ElfHeader,
+    events::{self, Value},
+    fs::{self, File},
+    library_cache::LibraryCache,
+    linux::environment_variables::EnvironmentIter,
+    shared_object::SharedObject,
+    statistics, trace,
+};
+
+/// The system directories, searched last and the only ones trusted in secure mode.
+pub const TRUSTED_DIRECTORIES: [&str; 6] = [
+    "/lib/x86_64-linux-gnu",
+    "/usr/lib/x86_64-linux-gnu",
+    "/lib64",
+    "/usr/lib64",
+    "/lib",
+    "/usr/lib",
+];
+
+/// The set-user-ID bit, preloads need it to be trusted in secure mode.
+const S_ISUID: u32 = 0o4000;
+
+/// Where to look for libraries, and in what order.
+///
+/// For a name without a slash, the search goes (see `ld.so(8)`):
+/// 1. `DT_RPATH` of the requesting object, then of the executable (skipped if the requester has a `DT_RUNPATH`).
+/// 2. `LD_LIBRARY_PATH` (or `--library-path`).
+/// 3. `DT_RUNPATH` of the requesting object.
+/// 4. `/etc/ld.so.cache` (unless `--inhibit-cache`).
+/// 5. The `TRUSTED_DIRECTORIES`.
+pub struct LibrarySearch {
+    pub library_path: Vec<&'static str>,
+    /// Objects whose `DT_RPATH` and `DT_RUNPATH` we ignore (`--inhibit-rpath`).
+    pub inhibit_rpath: Vec<&'static str>,
+    pub cache: Option<LibraryCache>,
+    pub secure: bool,
+}
+
+impl LibrarySearch {
+    pub fn new(environment: EnvironmentIter, options: &Options, secure: bool) -> Self {
+        let mut library_path = Vec::new();
+        for (name, value) in environment {
+            // In secure mode, the only places we look are the ones the program (or the system) chose.
+            if name == "LD_LIBRARY_PATH" && !secure {
+                library_path.extend(value.split([':', ';']));
+            }
+        }
+        // NOTE: Like glibc, `--library-path` replaces `LD_LIBRARY_PATH` rather than adding to it.
+        if let Some(path) = options.library_path.filter(|_| !secure) {
+            library_path = path.split([':', ';']).collect();
+        }
+
+        let inhibit_rpath = match options.inhibit_rpath {
+            Some(names) => names
+                .split([':', ' '])
+                .filter(|name| !name.is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
+        let cache = if options.inhibit_cache {
+            None
+        } else {
+            LibraryCache::load()
+        };
+
+        Self {
+            library_path,
+            inhibit_rpath,
+            cache,
+            secure,
+        }
+    }
+
+    /// Searches for the library `name` on behalf of `requester`, returning the opened file and the path it was found at.
+    ///
+    /// If it wasn't found, returns why: `ENOENT`, unless a candidate failed for a more telling reason (like `EACCES`).
+    pub fn find(
+        &self,
+        name: &str,
+        requester: &SharedObject,
+        executable: &SharedObject,
+    ) -> Result<(File, String), Errno> {
+        let _span = trace::span(trace::CATEGORY_SEARCH, name);
+        debug_println!(DEBUG_LIBS, "find library=", name, " [0]; searching");
+        let mut failure = ENOENT;
+        let found = self.search(name, requester, executable, &mut failure);
+        debug_println!(DEBUG_LIBS, "");
+        search_result(name, requester, &found);
+        found.ok_or(failure)
+    }
+
+    fn search(
+        &self,
+        name: &str,
+        requester: &SharedObject,
+        executable: &SharedObject,
+        failure: &mut Errno,
+    ) -> Option<(File, String)> {
+        if name.contains('/') {
+            let found = open_library(name, failure);
+            search_attempt(name, name, found.is_some());
+            return found.map(|file| (file, name.to_string()));
+        }
+
+        // 1. DT_RPATH:
+        if requester.runpath.is_none() {
+            if let Some(found) =
+                self.search_object_path(name, requester, requester.rpath, "RPATH", failure)
+            {
+                return Some(found);
+            }
+            if executable.runpath.is_none() {
+                if let Some(found) =
+                    self.search_object_path(name, executable, executable.rpath, "RPATH", failure)
+                {
+                    return Some(found);
+                }
+            }
+        }
+
+        // 2. LD_LIBRARY_PATH:
+        if !self.library_path.is_empty() {
+            debug_println!(
+                DEBUG_LIBS,
+                " search path=",
+                &self.library_path.join(":"),
+                "\t\t(LD_LIBRARY_PATH)"
+            );
+        }
+        for directory in &self.library_path {
+            if let Some(found) = search_directory(name, directory, failure) {
+                return Some(found);
+            }
+        }
+
+        // 3. DT_RUNPATH:
+        if let Some(found) =
+            self.search_object_path(name, requester, requester.runpath, "RUNPATH", failure)
+        {
+            return Some(found);
+        }
+
+        // 4. /etc/ld.so.cache:
+        if self.cache.is_some() {
+            debug_println!(DEBUG_LIBS, " search cache=/etc/ld.so.cache");
+        }
+        if let Some(path) = self.cache.as_ref().and_then(|cache| cache.find(name)) {
+            debug_println!(DEBUG_LIBS, "  trying file=", path);
+            let found = open_library(path, failure);
+            search_attempt(name, path, found.is_some());
+            if let Some(file) = found {
+                return Some((file, path.to_string()));
+            }
+        }
+
+        // 5. System Directories:
+        self.find_trusted(name, failure)
+    }
+
+    /// Searches only the `TRUSTED_DIRECTORIES` for `name`.
+    pub fn find_trusted(&self, name: &str, failure: &mut Errno) -> Option<(File, String)> {
+        debug_println!(
+            DEBUG_LIBS,
+            " search path=",
+            &TRUSTED_DIRECTORIES.join(":"),
+            "\t\t(system search path)"
+        );
+        for directory in TRUSTED_DIRECTORIES {
+            if let Some(found) = search_directory(name, directory, failure) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Searches for a library named by `LD_PRELOAD`, `--preload` or `/etc/ld.so.preload`.
+    ///
+    /// In secure mode, we can't trust whoever set the environment, so unless the name comes from a `trusted` source (the
+    /// file only root can write), only set-user-ID libraries in the `TRUSTED_DIRECTORIES` are allowed.
+    pub fn find_preload(
+        &self,
+        name: &str,
+        executable: &SharedObject,
+        trusted: bool,
+    ) -> Option<(File, String)> {
+        if !self.secure || trusted {
+            return self.find(name, executable, executable).ok();
+        }
+
+        if name.contains('/') {
+            return None;
+        }
+        let _span = trace::span(trace::CATEGORY_SEARCH, name);
+        debug_println!(DEBUG_LIBS, "find library=", name, " [0]; searching");
+        // NOTE: Why doesn't matter, a preload that can't be loaded is only ever reported as that.
+        let mut failure = ENOENT;
+        let found = self.find_trusted(name, &mut failure);
+        debug_println!(DEBUG_LIBS, "");
+        search_result(name, executable, &found);
+        let (file, path) = found?;
+        let metadata = file.metadata().ok()?;
+        (metadata.st_mode & S_ISUID != 0).then_some((file, path))
+    }
+
+    /// Searches a colon separated list of directories from an object's string table (`DT_RPATH` or `DT_RUNPATH`).
+    fn search_object_path(
+        &self,
+        name: &str,
+        shared_object: &SharedObject,
+        path_index: Option<usize>,
+        kind: &str,
+        failure: &mut Errno,
+    ) -> Option<(File, String)> {
+        let search_path = unsafe { shared_object.string_table.get(path_index?) };
+        if self.is_rpath_inhibited(shared_object) {
+            return None;
+        }
+        let origin = origin(shared_object);
+
+        let mut directories = Vec::new();
+        for directory in search_path.split(':') {
+            let expanded = expand_dynamic_string_tokens(directory, &origin);
+            // In secure mode, `$ORIGIN` could point anywhere someone managed to hard link the program to:
+            if self.secure && directory.contains("ORIGIN") && !is_trusted(&expanded) {
+                continue;
+            }
+            directories.push(expanded);
+        }
+        debug_println!(
+            DEBUG_LIBS,
+            " search path=",
+            &directories.join(":"),
+            "\t\t(",
+            kind,
+            " from file ",
+            shared_object.display_name(),
+            ")"
+        );
+
+        for directory in &directories {
+            if let Some(found) = search_directory(name, directory, failure) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Whether `shared_object` was named in `--inhibit-rpath`, by its path, file name, or soname.
+    fn is_rpath_inhibited(&self, shared_object: &SharedObject) -> bool {
+        let path = shared_object.path();
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        self.inhibit_rpath
+            .iter()
+            .any(|&name| name == path || name == file_name || shared_object.soname() == Some(name))
+    }
+}
+
+/// Whether `directory` is one of the `TRUSTED_DIRECTORIES`.
+pub fn is_trusted(directory: &str) -> bool {
+    let directory = directory.trim_end_matches('/');
+    TRUSTED_DIRECTORIES.contains(&directory)
+}
+
+fn search_directory(name: &str, directory: &str, failure: &mut Errno) -> Option<(File, String)> {
+    // An empty entry means the current working directory.
+    let directory = if directory.is_empty() { "." } else { directory };
+
+    let path = format!("{}/{}", directory.trim_end_matches('/'), name);
+    debug_println!(DEBUG_LIBS, "  trying file=", &path);
+    let found = open_library(&path, failure);
+    search_attempt(name, &path, found.is_some());
+    found.map(|file| (file, path))
+}
+
+fn search_attempt(name: &str, path: &str, found: bool) {
+    events::emit(
+        "search_attempt",
+        &[
+            ("library", Value::String(name)),
+            ("path", Value::String(path)),
+            ("found", Value::Bool(found)),
+        ],
+    );
+}
+
+fn search_result(name: &str, requester: &SharedObject, found: &Option<(File, String)>) {
+    events::emit(
+        "search_result",
+        &[
+            ("library", Value::String(name)),
+            ("requester", Value::String(requester.display_name())),
+            (
+                "path",
+                events::optional(found.as_ref().map(|(_, path)| path.as_str())),
+            ),
+        ],
+    );
+}
+
+/// Opens `path` if it's an ELF file we can actually load; anything else is skipped so the search can carry on.
+///
+/// Why it was skipped goes in `failure`, if that says more than what's already there.
+fn open_library(path: &str, failure: &mut Errno) -> Option<File> {
+    match try_open_library(path) {
+        Ok(file) => Some(file),
+        // Not being there is the least telling reason, anything else (like `EACCES`) is kept over it:
+        Err(errno) => {
+            if *failure == ENOENT && !matches!(errno, ENOENT | ENOTDIR) {
+                *failure = errno;
+            }
+            None
+        }
+    }
+}
+
+fn try_open_library(path: &str) -> Result<File, Errno> {
+    let file = File::open(path)?;
+    statistics::count_file_opened();
+
+    let mut header = ElfHeader::default();
+    let as_bytes = unsafe {
+        core::slice::from_raw_parts_mut(
+            &mut header as *mut ElfHeader as *mut u8,
+            size_of::<ElfHeader>(),
+        )
+    };
+    // A file too short to have an ELF header (`EIO`, we read nothing) isn't one:
+    file.read_exact_at(as_bytes, 0)
+        .map_err(|errno| if errno == EIO { ENOEXEC } else { errno })?;
+
+    const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+    const ELF_CLASS_64: u8 = 2;
+    const EM_X86_64: u16 = 62;
+    if header.e_ident[..4] == ELF_MAGIC
+        && header.e_ident[4] == ELF_CLASS_64
+        && header.e_machine == EM_X86_64
+    {
+        Ok(file)
+    } else {
+        Err(ENOEXEC)
+    }
+}
+
+/// The directory an object was loaded from, which is what `$ORIGIN` expands to.
+pub fn origin(shared_object: &SharedObject) -> String {
+    // The executable's name is empty, but the kernel knows where it came from:
+    let path = match shared_object.path() {
+        "" => match fs::read_link("/proc/self/exe") {
+            Ok(path) => path,
+            Err(_) => return String::new(),
+        },
+        path => path.to_string(),
+    };
+
+    match path.rsplit_once('/') {
+        Some(("", _)) => "/".to_string(),
+        Some((directory, _)) => directory.to_string(),
+        None => ".".to_string(),
+    }
+}
+
+/// Expands `$ORIGIN`, `$LIB` and `$PLATFORM` (or their `${...}` forms) in a search path.
+pub fn expand_dynamic_string_tokens(directory: &str, origin: &str) -> String {
+    directory
+        .replace("${ORIGIN}", origin)
+        .replace("$ORIGIN", origin)
+        .replace("${LIB}", "lib64")
+        .replace("$LIB", "lib64")
+        .replace("${PLATFORM}", "x86_64")
+        .replace("$PLATFORM", "x86_64")
+}
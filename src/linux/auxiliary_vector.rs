@@ -1,15 +1,66 @@
-use core::ffi::c_void;
+use core::{
+    cell::UnsafeCell,
+    ffi::{c_char, CStr},
+};
 
 use super::environment_variables::EnvironmentIter;
+use crate::{
+    elf::{header::ElfHeader, program_header::ProgramHeader},
+    io_macros::*,
+    syscall_format::Hex,
+};
 
+// Every `AT_*` type Linux defines, see `include/uapi/linux/auxvec.h` (and the architecture specific `asm/auxvec.h`):
 pub const AT_NULL: usize = 0;
+pub const AT_IGNORE: usize = 1;
+pub const AT_EXECFD: usize = 2;
 pub const AT_PHDR: usize = 3;
 pub const AT_PHENT: usize = 4;
 pub const AT_PHNUM: usize = 5;
 pub const AT_PAGE_SIZE: usize = 6;
 pub const AT_BASE: usize = 7;
+pub const AT_FLAGS: usize = 8;
 pub const AT_ENTRY: usize = 9;
+pub const AT_NOTELF: usize = 10;
+pub const AT_UID: usize = 11;
+pub const AT_EUID: usize = 12;
+pub const AT_GID: usize = 13;
+pub const AT_EGID: usize = 14;
+pub const AT_PLATFORM: usize = 15;
+pub const AT_HWCAP: usize = 16;
+pub const AT_CLKTCK: usize = 17;
+pub const AT_FPUCW: usize = 18;
+pub const AT_DCACHEBSIZE: usize = 19;
+pub const AT_ICACHEBSIZE: usize = 20;
+pub const AT_UCACHEBSIZE: usize = 21;
+pub const AT_IGNOREPPC: usize = 22;
+pub const AT_SECURE: usize = 23;
+pub const AT_BASE_PLATFORM: usize = 24;
 pub const AT_RANDOM: usize = 25;
+pub const AT_HWCAP2: usize = 26;
+pub const AT_RSEQ_FEATURE_SIZE: usize = 27;
+pub const AT_RSEQ_ALIGN: usize = 28;
+pub const AT_HWCAP3: usize = 29;
+pub const AT_HWCAP4: usize = 30;
+pub const AT_EXECFN: usize = 31;
+pub const AT_SYSINFO: usize = 32;
+pub const AT_SYSINFO_EHDR: usize = 33;
+pub const AT_L1I_CACHESHAPE: usize = 34;
+pub const AT_L1D_CACHESHAPE: usize = 35;
+pub const AT_L2_CACHESHAPE: usize = 36;
+pub const AT_L3_CACHESHAPE: usize = 37;
+pub const AT_L1I_CACHESIZE: usize = 40;
+pub const AT_L1I_CACHEGEOMETRY: usize = 41;
+pub const AT_L1D_CACHESIZE: usize = 42;
+pub const AT_L1D_CACHEGEOMETRY: usize = 43;
+pub const AT_L2_CACHESIZE: usize = 44;
+pub const AT_L2_CACHEGEOMETRY: usize = 45;
+pub const AT_L3_CACHESIZE: usize = 46;
+pub const AT_L3_CACHEGEOMETRY: usize = 47;
+pub const AT_MINSIGSTKSZ: usize = 51;
+
+/// One more than the largest type we know, everything past it is ignored.
+const AT_COUNT: usize = AT_MINSIGSTKSZ + 1;
 
 /// A union resolved by the a_type field of the parent auxiliary vector item.
 #[repr(C)]
@@ -81,6 +132,27 @@ impl AuxiliaryVectorIter {
     }
 }
 
+/// Overwrites the value of every `a_type` item in the auxiliary vector starting at `auxiliary_vector_pointer`, and in
+/// our copy of it.
+pub unsafe fn overwrite(
+    auxiliary_vector_pointer: *mut AuxiliaryVectorItem,
+    a_type: usize,
+    a_val: usize,
+) {
+    let mut item = auxiliary_vector_pointer;
+    while (*item).a_type != AT_NULL {
+        if (*item).a_type == a_type {
+            (*item).a_un.a_val = a_val;
+        }
+        item = item.add(1);
+    }
+
+    let snapshot = AUXILIARY_VECTOR.0.get();
+    if a_type < AT_COUNT && (*snapshot).present & (1 << a_type) != 0 {
+        (*snapshot).values[a_type] = a_val;
+    }
+}
+
 impl Iterator for AuxiliaryVectorIter {
     type Item = AuxiliaryVectorItem;
 
@@ -98,3 +170,253 @@ impl Iterator for AuxiliaryVectorIter {
         Some(item)
     }
 }
+
+/// Every entry of the auxiliary vector, copied out so it can be looked up by type.
+pub struct AuxiliaryVector {
+    values: [usize; AT_COUNT],
+    /// Bit `n` is set if there was an entry of type `n`, a value of zero doesn't tell.
+    present: u64,
+}
+
+impl AuxiliaryVector {
+    /// The value of the `a_type` entry, if there was one.
+    pub fn get(&self, a_type: usize) -> Option<usize> {
+        if a_type >= AT_COUNT || self.present & (1 << a_type) == 0 {
+            return None;
+        }
+        Some(self.values[a_type])
+    }
+
+    fn value(&self, a_type: usize) -> usize {
+        self.get(a_type).unwrap_or(0)
+    }
+
+    /// NOTE: A cast rather than `with_addr`, the kernel's pointers have to keep their provenance to be dereferenced.
+    fn pointer<T>(&self, a_type: usize) -> *const T {
+        self.value(a_type) as *const T
+    }
+
+    fn string(&self, a_type: usize) -> Option<&'static CStr> {
+        let pointer = self.pointer::<c_char>(a_type);
+        (!pointer.is_null()).then(|| unsafe { CStr::from_ptr(pointer) })
+    }
+
+    /// Where the interpreter (us) was loaded, null if the kernel ran us as the program.
+    pub fn base(&self) -> *const () {
+        self.pointer(AT_BASE)
+    }
+
+    /// The executable's entry point.
+    pub fn entry(&self) -> *const () {
+        self.pointer(AT_ENTRY)
+    }
+
+    /// The executable's program header table.
+    pub fn program_header_table(&self) -> (*const ProgramHeader, usize) {
+        (self.pointer(AT_PHDR), self.value(AT_PHNUM))
+    }
+
+    pub fn program_header_size(&self) -> usize {
+        self.value(AT_PHENT)
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.value(AT_PAGE_SIZE)
+    }
+
+    /// 16 random bytes from the kernel, for the stack protector and pointer guard.
+    pub fn random_bytes(&self) -> *const [u8; 16] {
+        self.pointer(AT_RANDOM)
+    }
+
+    /// The in-memory ELF image of the vDSO, null if there isn't one.
+    pub fn vdso(&self) -> *const ElfHeader {
+        self.pointer(AT_SYSINFO_EHDR)
+    }
+
+    /// Whether the kernel wants the program run in secure mode.
+    pub fn secure(&self) -> bool {
+        self.value(AT_SECURE) != 0
+    }
+
+    /// The real and effective user and group IDs.
+    pub fn ids(&self) -> [usize; 4] {
+        [
+            self.value(AT_UID),
+            self.value(AT_EUID),
+            self.value(AT_GID),
+            self.value(AT_EGID),
+        ]
+    }
+
+    /// The processor's capabilities, as bits of `cpuid` on x86_64.
+    pub fn hwcap(&self) -> usize {
+        self.value(AT_HWCAP)
+    }
+
+    pub fn hwcap2(&self) -> usize {
+        self.value(AT_HWCAP2)
+    }
+
+    /// The name of the platform, `x86_64`.
+    pub fn platform(&self) -> Option<&'static CStr> {
+        self.string(AT_PLATFORM)
+    }
+
+    /// The path the program was executed by.
+    pub fn execfn(&self) -> Option<&'static CStr> {
+        self.string(AT_EXECFN)
+    }
+
+    /// How many clock ticks `times` counts per second.
+    pub fn clock_ticks(&self) -> usize {
+        self.value(AT_CLKTCK)
+    }
+
+    /// The smallest stack a signal handler can run on, on this processor.
+    pub fn minimum_signal_stack_size(&self) -> usize {
+        self.value(AT_MINSIGSTKSZ)
+    }
+
+    /// The size and alignment of the kernel's `struct rseq`, for registering restartable sequences.
+    pub fn rseq_feature_size(&self) -> usize {
+        self.value(AT_RSEQ_FEATURE_SIZE)
+    }
+
+    pub fn rseq_align(&self) -> usize {
+        self.value(AT_RSEQ_ALIGN)
+    }
+
+    /// Prints every entry with its name, like `LD_SHOW_AUXV` does:
+    ///
+    /// ```text
+    /// AT_SYSINFO_EHDR:      0x7ffd5b5f5000
+    /// AT_PAGESZ:            4096
+    /// ```
+    pub fn show(&self) {
+        for (a_type, name, format) in NAMES {
+            let Some(value) = self.get(a_type) else {
+                continue;
+            };
+            // Values line up in a column, the longest name is `AT_L1I_CACHEGEOMETRY:`:
+            let padding = &"                      "[name.len() + 4..];
+            match format {
+                Format::Decimal => syscall_println!("AT_", name, ":", padding, value),
+                Format::Hexadecimal => {
+                    syscall_println!("AT_", name, ":", padding, Hex(value as u64))
+                }
+                Format::String => match self.string(a_type).map(CStr::to_str) {
+                    Some(Ok(string)) => syscall_println!("AT_", name, ":", padding, string),
+                    _ => syscall_println!("AT_", name, ":", padding, Hex(value as u64)),
+                },
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Decimal,
+    Hexadecimal,
+    String,
+}
+
+/// The names `LD_SHOW_AUXV` prints entries under, and how their values are shown; the order is glibc's.
+const NAMES: [(usize, &str, Format); 46] = [
+    (AT_SYSINFO_EHDR, "SYSINFO_EHDR", Format::Hexadecimal),
+    (AT_MINSIGSTKSZ, "MINSIGSTKSZ", Format::Decimal),
+    (AT_HWCAP, "HWCAP", Format::Hexadecimal),
+    (AT_PAGE_SIZE, "PAGESZ", Format::Decimal),
+    (AT_CLKTCK, "CLKTCK", Format::Decimal),
+    (AT_PHDR, "PHDR", Format::Hexadecimal),
+    (AT_PHENT, "PHENT", Format::Decimal),
+    (AT_PHNUM, "PHNUM", Format::Decimal),
+    (AT_BASE, "BASE", Format::Hexadecimal),
+    (AT_FLAGS, "FLAGS", Format::Hexadecimal),
+    (AT_ENTRY, "ENTRY", Format::Hexadecimal),
+    (AT_NOTELF, "NOTELF", Format::Hexadecimal),
+    (AT_UID, "UID", Format::Decimal),
+    (AT_EUID, "EUID", Format::Decimal),
+    (AT_GID, "GID", Format::Decimal),
+    (AT_EGID, "EGID", Format::Decimal),
+    (AT_SECURE, "SECURE", Format::Decimal),
+    (AT_RANDOM, "RANDOM", Format::Hexadecimal),
+    (AT_HWCAP2, "HWCAP2", Format::Hexadecimal),
+    (AT_HWCAP3, "HWCAP3", Format::Hexadecimal),
+    (AT_HWCAP4, "HWCAP4", Format::Hexadecimal),
+    (AT_EXECFN, "EXECFN", Format::String),
+    (AT_PLATFORM, "PLATFORM", Format::String),
+    (AT_BASE_PLATFORM, "BASE_PLATFORM", Format::String),
+    (AT_RSEQ_FEATURE_SIZE, "RSEQ_FEATURE_SIZE", Format::Decimal),
+    (AT_RSEQ_ALIGN, "RSEQ_ALIGN", Format::Decimal),
+    (AT_EXECFD, "EXECFD", Format::Decimal),
+    (AT_SYSINFO, "SYSINFO", Format::Hexadecimal),
+    (AT_FPUCW, "FPUCW", Format::Hexadecimal),
+    (AT_DCACHEBSIZE, "DCACHEBSIZE", Format::Decimal),
+    (AT_ICACHEBSIZE, "ICACHEBSIZE", Format::Decimal),
+    (AT_UCACHEBSIZE, "UCACHEBSIZE", Format::Decimal),
+    (AT_IGNOREPPC, "IGNOREPPC", Format::Decimal),
+    (AT_L1I_CACHESHAPE, "L1I_CACHESHAPE", Format::Hexadecimal),
+    (AT_L1D_CACHESHAPE, "L1D_CACHESHAPE", Format::Hexadecimal),
+    (AT_L2_CACHESHAPE, "L2_CACHESHAPE", Format::Hexadecimal),
+    (AT_L3_CACHESHAPE, "L3_CACHESHAPE", Format::Hexadecimal),
+    (AT_L1I_CACHESIZE, "L1I_CACHESIZE", Format::Decimal),
+    (
+        AT_L1I_CACHEGEOMETRY,
+        "L1I_CACHEGEOMETRY",
+        Format::Hexadecimal,
+    ),
+    (AT_L1D_CACHESIZE, "L1D_CACHESIZE", Format::Decimal),
+    (
+        AT_L1D_CACHEGEOMETRY,
+        "L1D_CACHEGEOMETRY",
+        Format::Hexadecimal,
+    ),
+    (AT_L2_CACHESIZE, "L2_CACHESIZE", Format::Decimal),
+    (AT_L2_CACHEGEOMETRY, "L2_CACHEGEOMETRY", Format::Hexadecimal),
+    (AT_L3_CACHESIZE, "L3_CACHESIZE", Format::Decimal),
+    (AT_L3_CACHEGEOMETRY, "L3_CACHEGEOMETRY", Format::Hexadecimal),
+    (AT_IGNORE, "IGNORE", Format::Hexadecimal),
+];
+
+struct Snapshot(UnsafeCell<AuxiliaryVector>);
+
+// Only written during startup, while there is just the one thread.
+unsafe impl Sync for Snapshot {}
+
+/// The program's auxiliary vector; when we load the program ourselves `overwrite` keeps it describing the program
+/// rather than us.
+static AUXILIARY_VECTOR: Snapshot = Snapshot(UnsafeCell::new(AuxiliaryVector {
+    values: [0; AT_COUNT],
+    present: 0,
+}));
+
+/// Copies the auxiliary vector the kernel gave us into `AUXILIARY_VECTOR`.
+///
+/// NOTE: It's filled in place rather than built on the stack, zeroing one there would call `memset` through the GOT
+/// and we aren't relocated yet.
+pub unsafe fn initialize(items: AuxiliaryVectorIter) -> &'static AuxiliaryVector {
+    let auxiliary_vector = AUXILIARY_VECTOR.0.get();
+    for item in items {
+        if item.a_type < AT_COUNT {
+            (*auxiliary_vector).values[item.a_type] = item.a_un.a_val;
+            (*auxiliary_vector).present |= 1 << item.a_type;
+        }
+    }
+    &*auxiliary_vector
+}
+
+/// The program's auxiliary vector, empty until `initialize`.
+pub fn get() -> &'static AuxiliaryVector {
+    unsafe { &*AUXILIARY_VECTOR.0.get() }
+}
+
+/// Prints the auxiliary vector if `LD_SHOW_AUXV` is set, unless the program is `secure`.
+///
+/// NOTE: Like glibc, it only has to be set, even `LD_SHOW_AUXV=` does.
+pub fn show_if_requested(mut environment: EnvironmentIter, secure: bool) {
+    let show = environment.any(|(name, _)| name == "LD_SHOW_AUXV");
+    if show && !secure {
+        get().show();
+    }
+}
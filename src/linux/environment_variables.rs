@@ -1,6 +1,49 @@
 use core::{slice, str};
 
-use crate::io_macros::*;
+use crate::{io_macros::*, linux::auxiliary_vector::AT_NULL};
+
+/// Variables that let whoever starts a program change how it behaves, so they are removed in secure mode.
+///
+/// This matches glibc's `unsecure-envvars.h`.
+pub const UNSECURE_ENVIRONMENT_VARIABLES: [&str; 37] = [
+    "GCONV_PATH",
+    "GETCONF_DIR",
+    "GLIBC_TUNABLES",
+    "HOSTALIASES",
+    "LD_AUDIT",
+    "LD_BIND_NOT",
+    "LD_BIND_NOW",
+    "LD_DEBUG",
+    "LD_DEBUG_OUTPUT",
+    "LD_DYNAMIC_WEAK",
+    "LD_HWCAP_MASK",
+    "LD_LIBRARY_PATH",
+    "LD_ORIGIN_PATH",
+    "LD_PRELOAD",
+    "LD_PROFILE",
+    "LD_SHOW_AUXV",
+    "LD_USE_LOAD_BIAS",
+    "LOCALDOMAIN",
+    "LOCPATH",
+    "MALLOC_ARENA_MAX",
+    "MALLOC_ARENA_TEST",
+    "MALLOC_CHECK_",
+    "MALLOC_MMAP_MAX_",
+    "MALLOC_MMAP_THRESHOLD_",
+    "MALLOC_PERTURB_",
+    "MALLOC_TCACHE_COUNT",
+    "MALLOC_TCACHE_MAX",
+    "MALLOC_TCACHE_UNSORTED_LIMIT",
+    "MALLOC_TOP_PAD_",
+    "MALLOC_TRACE",
+    "MALLOC_TRIM_THRESHOLD_",
+    "NIS_PATH",
+    "NLSPATH",
+    "RESOLV_HOST_CONF",
+    "RES_OPTIONS",
+    "TMPDIR",
+    "TZDIR",
+];
 
 /// An iterator over a null-terminated list of pointers to embedded-null-byte-terminated strings representing environment variables.
 ///
@@ -53,6 +96,52 @@ impl EnvironmentIter {
     pub fn into_inner(self) -> *mut *mut u8 {
         self.0
     }
+
+    /// Removes every variable named in `names` from the environment, in place, so the program never sees them.
+    ///
+    /// The auxiliary vector is directly after the environment, so it has to be shifted down too;
+    /// otherwise anyone scanning for the end of the environment would find an empty auxiliary vector:
+    ///
+    /// ```no_run
+    /// |---------------------|     |---------------------|
+    /// | env_pointers...     |     | kept env_pointers...|
+    /// |---------------------|     |---------------------|
+    /// | null                |  -> | null                |
+    /// |---------------------|     |---------------------|
+    /// | auxiliary_vector... |     | auxiliary_vector... |
+    /// |---------------------|     |---------------------|
+    /// | ...                 |     | leftovers...        |
+    /// |---------------------|     |---------------------|
+    /// ```
+    pub unsafe fn remove(self, names: &[&str]) {
+        let (mut read, mut write) = (self.0 as *mut usize, self.0 as *mut usize);
+
+        while *read != 0 {
+            let keep = match Self(read as *mut *mut u8).next() {
+                Some((name, _)) => !names.contains(&name),
+                None => true,
+            };
+            if keep {
+                *write = *read;
+                write = write.add(1);
+            }
+            read = read.add(1);
+        }
+
+        // The null terminating the environment:
+        *write = 0;
+        (read, write) = (read.add(1), write.add(1));
+
+        // The auxiliary vector, up to and including `AT_NULL`:
+        loop {
+            let (a_type, a_val) = (*read, *read.add(1));
+            (*write, *write.add(1)) = (a_type, a_val);
+            (read, write) = (read.add(2), write.add(2));
+            if a_type == AT_NULL {
+                break;
+            }
+        }
+    }
 }
 
 impl Iterator for EnvironmentIter {
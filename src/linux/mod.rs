@@ -1,3 +1,4 @@
 pub mod page_size;
 pub mod auxiliary_vector;
 pub mod environment_variables;
+pub mod secure_execution;
@@ -1,13 +1,18 @@
-use std::sync::OnceLock;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
-pub static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+use crate::syscall_assert;
+
+/// Zero until it's set from `AT_PAGESZ`.
+pub static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
 
 pub(crate) fn set_page_size(page_size: usize) {
-    let _ = PAGE_SIZE.set(page_size);
+    PAGE_SIZE.store(page_size, Ordering::Relaxed);
 }
 
 pub(crate) fn get_page_size() -> usize {
-    *PAGE_SIZE.get().expect("Page size not initialized")
+    let page_size = PAGE_SIZE.load(Ordering::Relaxed);
+    syscall_assert!(page_size != 0, "page size not initialized");
+    page_size
 }
 
 pub(crate) fn get_page_start(address: usize) -> usize {
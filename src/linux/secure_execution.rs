@@ -0,0 +1,15 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the program is privileged (set-user-ID, set-group-ID, or has file capabilities) and its environment can't be trusted.
+pub static SECURE: AtomicBool = AtomicBool::new(false);
+
+/// Secure mode is on if the kernel says so (`AT_SECURE`), or the real and effective IDs differ.
+pub(crate) fn set_secure(at_secure: bool, ids: [usize; 4]) {
+    let [user_id, effective_user_id, group_id, effective_group_id] = ids;
+    let secure = at_secure || user_id != effective_user_id || group_id != effective_group_id;
+    SECURE.store(secure, Ordering::Relaxed);
+}
+
+pub(crate) fn is_secure() -> bool {
+    SECURE.load(Ordering::Relaxed)
+}
@@ -0,0 +1,213 @@
+use alloc::vec::Vec;
+
+use crate::{
+    arch::io,
+    cli::Options,
+    dependencies::{self, Dependency, Target},
+    linux::environment_variables::EnvironmentIter,
+    loaded_objects,
+    loader_lock::LOADER_LOCK,
+    shared_object::SharedObject,
+    symbol_lookup,
+};
+
+/// Whether we should list what the program would load instead of running it (`--list` or `LD_TRACE_LOADED_OBJECTS`).
+///
+/// NOTE: Like glibc, `LD_TRACE_LOADED_OBJECTS` only has to be set, its value doesn't matter.
+pub fn is_requested(mut environment: EnvironmentIter, options: &Options) -> bool {
+    options.list || environment.any(|(name, _)| name == "LD_TRACE_LOADED_OBJECTS")
+}
+
+/// Prints every loaded object the way `ldd` does, after the executable itself:
+///
+/// ```text
+///     linux-vdso.so.1 (0x00007ffc2d3f5000)
+///     libc.so.6 => /lib/x86_64-linux-gnu/libc.so.6 (0x00007f3a1c800000)
+///     /lib64/ld-linux-x86-64.so.2 (0x00007f3a1cb2e000)
+/// ```
+///
+/// Libraries that couldn't be found are listed as `\tname => not found`, where they would have been loaded: among the
+/// objects the entries processed around them loaded, or before us (the interpreter) if nothing was loaded after them.
+pub fn print_loaded_objects(dependencies: &[Dependency], interpreter: &SharedObject) {
+    let vdso = loaded_objects::vdso();
+    if let Some(vdso) = vdso {
+        print_line(None, vdso.path(), vdso.base.addr());
+    }
+
+    let mut not_found = NotFound::new(dependencies);
+    let guard = LOADER_LOCK.lock();
+    let mut index = 1;
+    while let Some(shared_object) = loaded_objects::get(&guard, index) {
+        index += 1;
+        // It already came first, like `ldd` lists it:
+        if vdso.is_some_and(|vdso| core::ptr::eq(vdso, shared_object)) {
+            continue;
+        }
+        // We are loaded after every entry was processed; preloads before any was.
+        if core::ptr::eq(shared_object, interpreter) {
+            not_found.print_until(dependencies.len());
+        } else if let Some(position) = loaded_by(dependencies, shared_object) {
+            not_found.print_until(position);
+        }
+        print_line(
+            requested_name(shared_object),
+            shared_object.path(),
+            shared_object.base.addr(),
+        );
+    }
+    not_found.print_until(dependencies.len());
+}
+
+/// The libraries that couldn't be found, printed in the order their entries were processed, each only once.
+struct NotFound<'a> {
+    dependencies: &'a [Dependency],
+    next: usize,
+    printed: Vec<&'static str>,
+}
+
+impl<'a> NotFound<'a> {
+    fn new(dependencies: &'a [Dependency]) -> Self {
+        Self {
+            dependencies,
+            next: 0,
+            printed: Vec::new(),
+        }
+    }
+
+    /// Prints the missing libraries of the entries before `position` that haven't been printed yet.
+    fn print_until(&mut self, position: usize) {
+        while self.next < position {
+            let dependency = &self.dependencies[self.next];
+            self.next += 1;
+            if !matches!(dependency.target, Target::NotFound)
+                || self.printed.contains(&dependency.name)
+            {
+                continue;
+            }
+            self.printed.push(dependency.name);
+            io::write(io::STD_OUT, "\t");
+            io::write(io::STD_OUT, dependency.name);
+            io::write(io::STD_OUT, " => not found\n");
+        }
+    }
+}
+
+/// The position of the entry that loaded `shared_object`, if one did.
+fn loaded_by(dependencies: &[Dependency], shared_object: &SharedObject) -> Option<usize> {
+    dependencies.iter().position(|dependency| {
+        matches!(dependency.target, Target::Loaded(target) if core::ptr::eq(target, shared_object))
+    })
+}
+
+/// The `DT_NEEDED` entry that pulled `shared_object` in, if any; preloads and we (the interpreter) don't have one.
+fn requested_name(shared_object: &SharedObject) -> Option<&'static str> {
+    let guard = LOADER_LOCK.lock();
+    let mut index = 0;
+    while let Some(requester) = loaded_objects::get(&guard, index) {
+        for name in requester.needed_library_names() {
+            if !dependencies::is_interpreter(name) && dependencies::goes_by(shared_object, name) {
+                return Some(name);
+            }
+        }
+        index += 1;
+    }
+    None
+}
+
+/// Prints `\tname => path (0xbase)`, or just `\tpath (0xbase)` if the name is the path.
+fn print_line(name: Option<&str>, path: &str, base: usize) {
+    io::write(io::STD_OUT, "\t");
+    if let Some(name) = name.filter(|&name| name != path) {
+        io::write(io::STD_OUT, name);
+        io::write(io::STD_OUT, " => ");
+    }
+    io::write(io::STD_OUT, path);
+    io::write(io::STD_OUT, &format!(" ({base:#018x})\n"));
+}
+
+/// Resolves every symbolic relocation without applying any, and reports the ones nothing defines (like `ldd -r`):
+///
+/// ```text
+/// undefined symbol: compress2, version ZLIB_1.2.0 (./program)
+/// ```
+///
+/// (The name and the object are separated by a tab.)
+/// Objects are checked in the order glibc relocates them: dependencies first, the executable last.
+/// Returns whether anything was undefined.
+pub unsafe fn verify_symbols(program_name: &str, interpreter: &SharedObject) -> bool {
+    let mut undefined = false;
+    for shared_object in symbol_lookup::relocation_order(interpreter) {
+        let _span = symbol_lookup::start_relocating(shared_object);
+        let mut reported = Vec::new();
+        for rela in shared_object.relocations.iter() {
+            let Some(binding) = symbol_lookup::resolve(shared_object, rela) else {
+                continue;
+            };
+            if binding.definition.is_some() || binding.weak || reported.contains(&binding.name) {
+                continue;
+            }
+            // NOTE: libc's private interface with glibc's dynamic linker isn't something the program can do anything about.
+            let file = binding.version.and_then(|version| version.file);
+            if file.is_some_and(dependencies::is_interpreter) {
+                continue;
+            }
+            reported.push(binding.name);
+            undefined = true;
+
+            io::write(io::STD_OUT, "undefined symbol: ");
+            io::write(io::STD_OUT, binding.name);
+            if let Some(version) = binding.version {
+                io::write(io::STD_OUT, ", version ");
+                io::write(io::STD_OUT, version.name);
+            }
+            io::write(io::STD_OUT, "\t(");
+            io::write(io::STD_OUT, display_name(shared_object, program_name));
+            io::write(io::STD_OUT, ")\n");
+        }
+    }
+    undefined
+}
+
+/// Lists the executable's `DT_NEEDED` libraries that none of its relocations bind to (like `ldd -u`).
+///
+/// Returns whether there were any.
+pub unsafe fn print_unused(executable: &'static SharedObject) -> bool {
+    let mut used = Vec::new();
+    for rela in executable.relocations.iter() {
+        let definition =
+            symbol_lookup::resolve(executable, rela).and_then(|binding| binding.definition);
+        if let Some(definition) = definition {
+            used.push(definition.shared_object as *const SharedObject);
+        }
+    }
+
+    let mut any_unused = false;
+    for name in executable.needed_library_names() {
+        if dependencies::is_interpreter(name) {
+            continue;
+        }
+        let Some(library) = dependencies::find_loaded(name) else {
+            continue;
+        };
+        if used.contains(&(library as *const SharedObject)) {
+            continue;
+        }
+
+        if !any_unused {
+            io::write(io::STD_OUT, "Unused direct dependencies:\n");
+            any_unused = true;
+        }
+        io::write(io::STD_OUT, "\t");
+        io::write(io::STD_OUT, library.path());
+        io::write(io::STD_OUT, "\n");
+    }
+    any_unused
+}
+
+/// The name to report an object under, the executable doesn't have one of its own unless we loaded it.
+fn display_name<'a>(shared_object: &'a SharedObject, program_name: &'a str) -> &'a str {
+    match shared_object.path() {
+        "" => program_name,
+        path => path,
+    }
+}
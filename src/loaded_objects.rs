@@ -0,0 +1,82 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    cell::UnsafeCell,
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, AtomicU64, AtomicUsize, Ordering},
+};
+
+use crate::{
+    exports::r_debug,
+    loader_lock::{LoaderLockGuard, LOADER_LOCK},
+    mapped_ranges,
+    shared_object::SharedObject,
+};
+
+/// Every object we have loaded, in load order (the executable first).
+///
+/// Entries are leaked, so references to them stay valid even if the list grows while someone is iterating it.
+struct LoadedObjects(UnsafeCell<Vec<&'static SharedObject>>);
+
+// Only accessed while holding the `LOADER_LOCK`.
+unsafe impl Sync for LoadedObjects {}
+
+static LOADED_OBJECTS: LoadedObjects = LoadedObjects(UnsafeCell::new(Vec::new()));
+
+// Counters exposed via `dl_phdr_info` so callers can cache their results and notice when objects come and go:
+static OBJECTS_ADDED: AtomicU64 = AtomicU64::new(0);
+static OBJECTS_REMOVED: AtomicU64 = AtomicU64::new(0);
+
+/// The module id the next object with thread local storage gets; like glibc, that makes the executable's 1 (if it has any).
+static NEXT_TLS_MODULE_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// The vDSO the kernel maps for us (`AT_SYSINFO_EHDR`), null if there isn't one.
+static VDSO: AtomicPtr<SharedObject> = AtomicPtr::new(null_mut());
+
+/// Registers a `SharedObject` as loaded, returning a reference that lives for the rest of the program.
+///
+/// Objects with thread local storage are given the next module id, in load order.
+pub fn add(mut shared_object: SharedObject) -> &'static SharedObject {
+    let _guard = LOADER_LOCK.lock();
+
+    if shared_object.has_thread_local_storage() {
+        shared_object.thread_local_block = Some(NEXT_TLS_MODULE_ID.fetch_add(1, Ordering::Relaxed));
+    }
+    let shared_object: &'static SharedObject = Box::leak(Box::new(shared_object));
+    unsafe { (&mut *LOADED_OBJECTS.0.get()).push(shared_object) };
+    OBJECTS_ADDED.fetch_add(1, Ordering::Relaxed);
+
+    let (start, end) = shared_object.mapped_bounds();
+    mapped_ranges::insert(start, end, shared_object.eh_frame_header(), shared_object);
+    unsafe { r_debug::link(shared_object) };
+
+    shared_object
+}
+
+/// Retrieves the loaded object at `index`; the guard proves the list can't change underneath us.
+pub fn get(_guard: &LoaderLockGuard, index: usize) -> Option<&'static SharedObject> {
+    unsafe { (&*LOADED_OBJECTS.0.get()).get(index).copied() }
+}
+
+/// The number of objects that have ever been loaded.
+pub fn added() -> u64 {
+    OBJECTS_ADDED.load(Ordering::Relaxed)
+}
+
+/// The number of objects that have ever been unloaded.
+pub fn removed() -> u64 {
+    OBJECTS_REMOVED.load(Ordering::Relaxed)
+}
+
+/// Registers the vDSO like any other loaded object, and remembers which one it is.
+///
+/// NOTE: It goes last, like in glibc it shouldn't interpose on anything; its symbols are versioned (`LINUX_2.6`) anyway.
+pub fn add_vdso(vdso: SharedObject) -> &'static SharedObject {
+    let vdso = add(vdso);
+    VDSO.store((vdso as *const SharedObject).cast_mut(), Ordering::Relaxed);
+    vdso
+}
+
+/// The vDSO, if the kernel gave us one.
+pub fn vdso() -> Option<&'static SharedObject> {
+    unsafe { VDSO.load(Ordering::Relaxed).as_ref() }
+}
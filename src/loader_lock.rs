@@ -0,0 +1,74 @@
+use core::{
+    cell::UnsafeCell,
+    hint::spin_loop,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::arch::thread_pointer::get_thread_pointer;
+
+/// The lock guarding every change to the set of loaded objects.
+pub static LOADER_LOCK: LoaderLock = LoaderLock::new();
+
+/// A recursive spin lock owned by whichever thread holds it. 🔒
+///
+/// Threads are told apart by their thread pointer, so this works for any thread the program creates without help from `libc`.
+/// It is recursive because callbacks (like the ones passed to `dl_iterate_phdr`) are allowed to call back into the loader.
+///
+/// NOTE: Before the thread pointer is set (in `allocate_tls_in_stomach`) it's null, and there is only the main thread, so
+/// it goes by `BEFORE_TLS` instead; null would look like `UNLOCKED`.
+pub struct LoaderLock {
+    owner: AtomicUsize,
+    depth: UnsafeCell<usize>,
+}
+
+// The `depth` is only ever touched by the thread that owns the lock.
+unsafe impl Sync for LoaderLock {}
+
+impl LoaderLock {
+    const UNLOCKED: usize = 0;
+    /// Who owns the lock if it was taken before the thread pointer was set, never a valid thread pointer.
+    const BEFORE_TLS: usize = 1;
+
+    pub const fn new() -> Self {
+        Self {
+            owner: AtomicUsize::new(Self::UNLOCKED),
+            depth: UnsafeCell::new(0),
+        }
+    }
+
+    /// Blocks until the current thread owns the lock, returning a guard that releases it when dropped.
+    pub fn lock(&'static self) -> LoaderLockGuard {
+        let thread = match unsafe { get_thread_pointer() }.addr() {
+            Self::UNLOCKED => Self::BEFORE_TLS,
+            thread => thread,
+        };
+
+        if self.owner.load(Ordering::Relaxed) != thread {
+            while self
+                .owner
+                .compare_exchange_weak(Self::UNLOCKED, thread, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                spin_loop();
+            }
+        }
+
+        unsafe { *self.depth.get() += 1 };
+        LoaderLockGuard(self)
+    }
+}
+
+/// Proof that the current thread holds the `LoaderLock`.
+pub struct LoaderLockGuard(&'static LoaderLock);
+
+impl Drop for LoaderLockGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let depth = self.0.depth.get();
+            *depth -= 1;
+            if *depth == 0 {
+                self.0.owner.store(LoaderLock::UNLOCKED, Ordering::Release);
+            }
+        }
+    }
+}
@@ -3,40 +3,72 @@
 #![feature(naked_functions)]
 #![feature(ptr_as_ref_unchecked)]
 #![feature(type_changing_struct_update)]
-#![no_main]
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
 #![allow(dead_code)]
 
+#[macro_use]
+extern crate alloc;
+
 use core::{
-    ptr::{null, null_mut},
+    ffi::{c_char, CStr},
     slice,
 };
-use std::{collections::HashMap, fs::File, path::Path};
 
+mod allocator;
 #[cfg_attr(target_arch = "x86_64", path = "arch/x86_64/mod.rs")]
 mod arch;
 
+mod audit;
 mod cli;
+mod collisions;
+mod debug;
+mod dependencies;
 mod elf;
+mod error;
+mod events;
+mod explain;
+mod exports;
+mod fs;
+mod graph;
 mod io_macros;
+mod json;
+mod library_cache;
+mod library_search;
 mod linux;
+mod list;
+mod loaded_objects;
+mod loader_lock;
+mod mapped_ranges;
+mod panic_handler;
+mod preload;
 mod shared_object;
 mod static_pie;
-
-use elf::program_header::ProgramHeader;
+mod statistics;
+mod symbol_lookup;
+mod sync;
+mod syscall_format;
+mod trace;
+
+use cli::Options;
+use elf::program_header::{ProgramHeader, PT_INTERP};
+use exports::r_debug;
 use io_macros::*;
 use linux::{
-    auxiliary_vector::{
-        AuxiliaryVectorIter, AT_BASE, AT_ENTRY, AT_PAGE_SIZE, AT_PHDR, AT_PHENT, AT_PHNUM,
-        AT_RANDOM,
-    },
-    environment_variables::EnvironmentIter,
-    page_size,
+    auxiliary_vector::{self, AuxiliaryVectorIter},
+    environment_variables::{EnvironmentIter, UNSECURE_ENVIRONMENT_VARIABLES},
+    page_size, secure_execution,
 };
+use library_search::LibrarySearch;
 use shared_object::SharedObject;
+use elf::relocate::Relocatable;
 use static_pie::StaticPie;
+use statistics::Phase;
 
 // This is where the magic happens, it's called by the architecture specific _start and returns the entry address when everything is set up:
 pub unsafe fn rust_main(stack_pointer: *mut usize) -> usize {
+    statistics::mark(Phase::Start);
+
     // Check that `stack_pointer` is where we expect it to be.
     syscall_debug_assert!(stack_pointer != core::ptr::null_mut());
     syscall_debug_assert!(stack_pointer.addr() & 0b1111 == 0);
@@ -45,54 +77,156 @@ pub unsafe fn rust_main(stack_pointer: *mut usize) -> usize {
     let argument_pointer = stack_pointer.add(1) as *mut *mut u8;
     syscall_debug_assert!((*argument_pointer.add(argument_count)).is_null());
 
-    let environment_vector = EnvironmentIter::from_stack_pointer(stack_pointer);
-    let auxiliary_vector = AuxiliaryVectorIter::from_environment_iter(environment_vector);
+    let mut environment_vector = EnvironmentIter::from_stack_pointer(stack_pointer);
+    let auxiliary_vector =
+        auxiliary_vector::initialize(AuxiliaryVectorIter::from_environment_iter(environment_vector));
 
     // Auxilary Vector:
-    let (mut base, mut entry, mut page_size) = (null(), null(), 0);
-    let mut pseudorandom_bytes: *const [u8; 16] = null_mut();
+    let mut base = auxiliary_vector.base();
+    let page_size = auxiliary_vector.page_size();
+    let pseudorandom_bytes = auxiliary_vector.random_bytes();
     // NOTE: The program headers in the auxiliary vector belong to the executable, not us.
-    let (mut program_header_pointer, mut program_header_count) = (null(), 0);
-    for value in auxiliary_vector {
-        match value.a_type {
-            AT_BASE => base = value.a_un.a_ptr,
-            AT_ENTRY => entry = value.a_un.a_ptr,
-            AT_PAGE_SIZE => page_size = value.a_un.a_val,
-            AT_RANDOM => pseudorandom_bytes = value.a_un.a_ptr as *const [u8; 16],
-            // Executable Stuff:
-            AT_PHDR => program_header_pointer = value.a_un.a_ptr as *const ProgramHeader,
-            AT_PHNUM => program_header_count = value.a_un.a_val,
-            #[cfg(debug_assertions)]
-            AT_PHENT => syscall_assert!(value.a_un.a_val == size_of::<ProgramHeader>()),
-            _ => (),
-        }
-    }
+    let (program_header_pointer, program_header_count) = auxiliary_vector.program_header_table();
+    syscall_debug_assert!(auxiliary_vector.program_header_size() == size_of::<ProgramHeader>());
 
     let program_header_table =
-        slice::from_raw_parts(program_header_pointer, program_header_count as usize);
+        slice::from_raw_parts(program_header_pointer, program_header_count);
+
+    // Without `AT_BASE` the kernel ran us as the program, so we have to load the real one ourselves:
+    let direct_invocation = base.is_null();
 
     // We are a static pie (position-independent-executable).
     // Relocate ourselves and initialize thread local storage:
-    let miros = if base.is_null() {
+    let miros = if direct_invocation {
         StaticPie::from_program_headers(&program_header_table, pseudorandom_bytes)
     } else {
         StaticPie::from_base(base, pseudorandom_bytes)
     };
+    base = miros.base();
     miros.relocate_to_oven().allocate_tls_in_stomach();
-    // NOTE: We can now use the Rust standard library.
+    // NOTE: We can now use thread locals.
+    statistics::mark(Phase::SelfRelocated);
 
     syscall_debug_assert!(page_size.is_power_of_two());
     syscall_debug_assert!(base.addr() & (page_size - 1) == 0);
     page_size::set_page_size(page_size);
+    secure_execution::set_secure(auxiliary_vector.secure(), auxiliary_vector.ids());
+    debug::initialize(environment_vector, secure_execution::is_secure());
+    trace::initialize(environment_vector, secure_execution::is_secure());
+    events::initialize(environment_vector, secure_execution::is_secure());
+    syscall_format::initialize(environment_vector);
+
+    // The vDSO tells the time without a system call, everything timed from here on goes through it:
+    let vdso = match auxiliary_vector.vdso().is_null() {
+        true => None,
+        false => Some(SharedObject::from_vdso(auxiliary_vector.vdso())),
+    };
+    if let Some(vdso) = &vdso {
+        arch::time::use_vdso(vdso);
+    }
 
-    if base == null() {
-        // TODO: Cli
-        arch::exit::exit(1);
+    let (shared_object, interpreter_name, options) = if direct_invocation {
+        let (shared_object, interpreter_name, options) = cli::load_executable(stack_pointer, base);
+        // The arguments shifted, and the environment with them:
+        environment_vector = EnvironmentIter::from_stack_pointer(stack_pointer);
+        (shared_object, interpreter_name, options)
+    } else {
+        // Like glibc, errors are reported under the name the program was started by:
+        if let Ok(program_name) = CStr::from_ptr(*argument_pointer as *const c_char).to_str() {
+            error::set_program_name(program_name);
+        }
+        let shared_object = SharedObject::from_headers(&program_header_table, pseudorandom_bytes);
+        events::object_mapped(&shared_object);
+
+        // We show up under whatever name the executable asked for us by:
+        let mut interpreter_name = c"";
+        for header in program_header_table {
+            if header.p_type == PT_INTERP {
+                interpreter_name =
+                    CStr::from_ptr(shared_object.base.wrapping_byte_add(header.p_vaddr).cast());
+            }
+        }
+        (shared_object, interpreter_name, Options::default())
+    };
+    // Once the executable is loaded, so it shows what the program will see:
+    auxiliary_vector::show_if_requested(environment_vector, secure_execution::is_secure());
+    if options.stats {
+        debug::enable(debug::DEBUG_STATISTICS);
     }
 
-    let shared_object = SharedObject::from_headers(&program_header_table, pseudorandom_bytes);
+    r_debug::initialize(base);
+    let shared_object = loaded_objects::add(shared_object);
+    r_debug::fill_debug_entry(shared_object);
+    statistics::mark(Phase::ExecutableLoaded);
 
-    
+    // Preloads go right after the executable so they come before everything else in the global scope:
+    let library_search =
+        LibrarySearch::new(environment_vector, &options, secure_execution::is_secure());
+    audit::ignore_audit_modules(environment_vector, &options);
+    preload::load_preloads(environment_vector, &options, shared_object, &library_search);
+
+    // Whoever started a privileged program doesn't get to influence it (or anything it starts) through the environment:
+    if secure_execution::is_secure() {
+        environment_vector.remove(&UNSECURE_ENVIRONMENT_VARIABLES);
+    }
+    let listing = list::is_requested(environment_vector, &options);
+    let dependencies = dependencies::load_needed_libraries(shared_object, &library_search, listing);
+
+    // NOTE: Like glibc, we go last; libraries can interpose on anything we export.
+    let interpreter = loaded_objects::add(SharedObject::from_base(base, interpreter_name));
+    // We relocated ourselves before there was anywhere to count it:
+    let own_relative_relocations = interpreter
+        .relocations
+        .iter()
+        .filter(|rela| rela.r_type() == arch::relocation::R_X86_64_RELATIVE)
+        .count();
+    interpreter.statistics.add(
+        statistics::Counter::RelativeRelocations,
+        own_relative_relocations,
+    );
+    if let Some(vdso) = vdso {
+        loaded_objects::add_vdso(vdso);
+    }
+    dependencies::debug_version_requirements();
+    statistics::mark(Phase::LibrariesLoaded);
+
+    // Whatever loading allocated and freed again (file names, search paths, ...) doesn't need to stay mapped while we
+    // report on or run the program:
+    allocator::release_unused();
+
+    // Everything is mapped, but nothing has been relocated or run yet:
+    if listing {
+        if options.unused || debug::is_enabled(debug::DEBUG_UNUSED) {
+            let any_unused = list::print_unused(shared_object);
+            finish(any_unused as usize);
+        }
+
+        if options.check_collisions {
+            let any_collisions = collisions::check_collisions(interpreter);
+            statistics::mark(Phase::SymbolsResolved);
+            finish(any_collisions as usize);
+        }
+        if let Some(query) = options.explain_symbol {
+            let referenced = explain::explain_symbol(query, interpreter);
+            statistics::mark(Phase::SymbolsResolved);
+            finish(!referenced as usize);
+        }
+        if let Some(format) = options.graph {
+            graph::print(format, &dependencies, interpreter);
+            finish(0);
+        }
+        list::print_loaded_objects(&dependencies, interpreter);
+        if options.verify_symbols {
+            let program_name = CStr::from_ptr(*(stack_pointer.add(1) as *const *const c_char));
+            let any_undefined = list::verify_symbols(&program_name.to_string_lossy(), interpreter);
+            statistics::mark(Phase::SymbolsResolved);
+            finish(any_undefined as usize);
+        }
+        finish(0);
+    }
+
+    symbol_lookup::resolve_all(interpreter);
+    statistics::mark(Phase::SymbolsResolved);
 
     // let linked_shared_objects: HashMap<&'static str, SharedObject> = HashMap::new();
     // for library in shared_object.libraries() {
@@ -102,5 +236,13 @@ pub unsafe fn rust_main(stack_pointer: *mut usize) -> usize {
     //     }
     // }
 
-    arch::exit::exit(0);
+    finish(0);
+}
+
+/// Exits with `status`, reporting statistics and writing the trace first if they were asked for.
+fn finish(status: usize) -> ! {
+    statistics::mark(Phase::Done);
+    statistics::report();
+    trace::write();
+    arch::exit::exit(status);
 }
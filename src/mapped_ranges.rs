@@ -0,0 +1,96 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    cell::UnsafeCell,
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+use crate::shared_object::SharedObject;
+
+/// The address range an object occupies, along with what unwinders want to know about it.
+#[derive(Clone, Copy)]
+pub struct MappedRange {
+    pub start: usize,
+    pub end: usize,
+    pub eh_frame_header: *const (),
+    pub shared_object: *const SharedObject,
+}
+
+/// Every `MappedRange`, sorted by start address so it can be binary searched.
+///
+/// Writers (which must hold the `LOADER_LOCK`) never modify a published table; they copy it, insert into the copy, and swap the pointer.
+/// That makes reads lock-free: an exception thrown on one thread never waits on a `dlopen` running on another.
+///
+/// Replaced tables are retired rather than freed, a reader might still be searching one. They are freed by the first
+/// writer to see no readers at all, so the only ones kept around are those replaced while a search was running.
+static MAPPED_RANGES: AtomicPtr<Vec<MappedRange>> = AtomicPtr::new(null_mut());
+
+/// How many `find`s are searching a table right now.
+static READERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Tables that have been replaced, waiting for the readers that might still be searching them.
+struct RetiredTables(UnsafeCell<Vec<*mut Vec<MappedRange>>>);
+
+// Only accessed while holding the `LOADER_LOCK`.
+unsafe impl Sync for RetiredTables {}
+
+static RETIRED_TABLES: RetiredTables = RetiredTables(UnsafeCell::new(Vec::new()));
+
+/// Adds the range `[start, end)` to the table; the caller must hold the `LOADER_LOCK`.
+pub fn insert(
+    start: usize,
+    end: usize,
+    eh_frame_header: *const (),
+    shared_object: *const SharedObject,
+) {
+    let current = MAPPED_RANGES.load(Ordering::Acquire);
+    let mut ranges = match unsafe { current.as_ref() } {
+        Some(current) => current.clone(),
+        None => Vec::new(),
+    };
+
+    let index = ranges.partition_point(|range| range.start < start);
+    ranges.insert(
+        index,
+        MappedRange {
+            start,
+            end,
+            eh_frame_header,
+            shared_object,
+        },
+    );
+
+    MAPPED_RANGES.store(Box::into_raw(Box::new(ranges)), Ordering::SeqCst);
+
+    let retired_tables = unsafe { &mut *RETIRED_TABLES.0.get() };
+    if !current.is_null() {
+        retired_tables.push(current);
+    }
+    // NOTE: Readers register before they load the pointer, so once there are none, any that come later can only find the
+    // table we just stored; `SeqCst` on both sides is what makes that hold.
+    if READERS.load(Ordering::SeqCst) == 0 {
+        for table in retired_tables.drain(..) {
+            drop(unsafe { Box::from_raw(table) });
+        }
+    }
+}
+
+/// Finds the range containing `address` without taking any locks.
+pub fn find(address: usize) -> Option<MappedRange> {
+    READERS.fetch_add(1, Ordering::SeqCst);
+    let range = search(
+        unsafe { MAPPED_RANGES.load(Ordering::SeqCst).as_ref() },
+        address,
+    );
+    READERS.fetch_sub(1, Ordering::Release);
+    range
+}
+
+fn search(ranges: Option<&Vec<MappedRange>>, address: usize) -> Option<MappedRange> {
+    let ranges = ranges?;
+
+    // The last range starting at or before `address` is the only one that can contain it:
+    let index = ranges.partition_point(|range| range.start <= address);
+    let range = *ranges.get(index.checked_sub(1)?)?;
+    (address < range.end).then_some(range)
+}
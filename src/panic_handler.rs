@@ -0,0 +1,84 @@
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+#[cfg(not(test))]
+use core::{fmt::Write as _, panic::PanicInfo};
+
+#[cfg(not(test))]
+use crate::{arch::exit, statistics, syscall_format::Writer};
+
+/// What we exit with when we panic: `EX_SOFTWARE` from `sysexits.h`, an internal error.
+///
+/// It is deliberately not `101` (what Rust programs and a failed `syscall_assert!` exit with) or `127` (what we exit with
+/// when the program can't be loaded), so a bug in miros can be told apart from either.
+pub const EXIT_PANIC: usize = 70;
+
+/// The object we were last busy with, so a panic can say which one it was about.
+static CURRENT_OBJECT: AtomicPtr<u8> = AtomicPtr::new(core::ptr::null_mut());
+static CURRENT_OBJECT_LENGTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Records that we are now working on `name`, for the panic report.
+pub fn set_current_object(name: &'static str) {
+    CURRENT_OBJECT_LENGTH.store(0, Ordering::Relaxed);
+    CURRENT_OBJECT.store(name.as_ptr().cast_mut(), Ordering::Relaxed);
+    CURRENT_OBJECT_LENGTH.store(name.len(), Ordering::Relaxed);
+}
+
+fn current_object() -> Option<&'static str> {
+    let pointer = CURRENT_OBJECT.load(Ordering::Relaxed);
+    let length = CURRENT_OBJECT_LENGTH.load(Ordering::Relaxed);
+    if pointer.is_null() || length == 0 {
+        return None;
+    }
+    // SAFETY: It came from a `&'static str`.
+    Some(unsafe { core::str::from_utf8_unchecked(core::slice::from_raw_parts(pointer, length)) })
+}
+
+/// Reports panics straight to standard error through system calls, and exits:
+///
+/// ```text
+/// miros: panicked at src/dependencies.rs:61:40: index out of bounds: the len is 3 but the index is 3
+///   while: loading libraries
+///   object: /lib/x86_64-linux-gnu/libc.so.6
+/// ```
+///
+/// It doesn't allocate or use thread local storage, the message is formatted straight into a buffer on the stack.
+/// NOTE: Formatting calls through function pointers, so a panic before we are relocated won't get this far; until then
+/// `syscall_assert!` is all there is.
+#[cfg(not(test))]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    let mut writer = Writer::new(2);
+    writer.write_bytes(b"miros: panicked");
+    if let Some(location) = info.location() {
+        let _ = write!(
+            writer,
+            " at {}:{}:{}",
+            location.file(),
+            location.line(),
+            location.column()
+        );
+    }
+    let _ = writeln!(
+        writer,
+        ": {}\n  while: {}",
+        info.message(),
+        statistics::current_phase()
+    );
+    if let Some(object) = current_object() {
+        let _ = writeln!(writer, "  object: {object}");
+    }
+    writer.flush();
+    exit::exit(EXIT_PANIC);
+}
+
+// We build with `panic = "abort"`, but the precompiled `core` and `alloc` still have unwinding paths that name these.
+// Nothing ever unwinds, so they are never called. (Unit tests link `std`, which brings the real ones.)
+
+#[cfg(not(test))]
+#[no_mangle]
+extern "C" fn rust_eh_personality() {}
+
+#[cfg(not(test))]
+#[no_mangle]
+extern "C" fn _Unwind_Resume() -> ! {
+    exit::exit(EXIT_PANIC);
+}
@@ -0,0 +1,67 @@
+use crate::{
+    arch::io, cli::Options, dependencies, events, fs, library_search::LibrarySearch,
+    linux::environment_variables::EnvironmentIter, shared_object::SharedObject,
+};
+
+/// A system wide list of libraries to preload, trusted because only root can write it.
+const PRELOAD_FILE: &str = "/etc/ld.so.preload";
+
+/// Loads the libraries named by `LD_PRELOAD` (separated by spaces or colons), then `--preload`, and then `/etc/ld.so.preload`.
+///
+/// This has to happen right after the executable is registered, before any `DT_NEEDED` libraries,
+/// that way preloads come first in the global scope and interpose on everything after them.
+pub unsafe fn load_preloads(
+    environment: EnvironmentIter,
+    options: &Options,
+    executable: &SharedObject,
+    library_search: &LibrarySearch,
+) {
+    for (name, value) in environment {
+        if name == "LD_PRELOAD" {
+            for library in value.split([' ', ':']) {
+                load_preload(library, "LD_PRELOAD", false, executable, library_search);
+            }
+        }
+    }
+    if let Some(value) = options.preload {
+        for library in value.split([' ', ':']) {
+            load_preload(library, "--preload", false, executable, library_search);
+        }
+    }
+
+    // NOTE: Most systems don't have one, so there is nothing to report if it's missing.
+    if let Ok(contents) = fs::read_to_string(PRELOAD_FILE) {
+        for library in contents.split([' ', '\t', '\n', ':']) {
+            load_preload(library, PRELOAD_FILE, true, executable, library_search);
+        }
+    }
+}
+
+/// `trusted` is whether `source` is, whoever started a privileged program only controls the environment and arguments.
+unsafe fn load_preload(
+    name: &str,
+    source: &str,
+    trusted: bool,
+    executable: &SharedObject,
+    library_search: &LibrarySearch,
+) {
+    if name.is_empty() || dependencies::find_loaded(name).is_some() {
+        return;
+    }
+
+    // A preload that can't be found isn't fatal, glibc just warns and carries on:
+    let Some((file, path)) = library_search.find_preload(name, executable, trusted) else {
+        events::error("cannot be preloaded (cannot open shared object file)", name);
+        io::write(io::STD_ERR, "ERROR: miros: object '");
+        io::write(io::STD_ERR, name);
+        io::write(io::STD_ERR, "' from ");
+        io::write(io::STD_ERR, source);
+        io::write(
+            io::STD_ERR,
+            " cannot be preloaded (cannot open shared object file): ignored.\n",
+        );
+        return;
+    };
+
+    dependencies::map_library(file, path);
+}
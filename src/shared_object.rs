@@ -1,26 +1,40 @@
 use core::ptr::null;
-use core::{ffi::c_void, slice};
-use std::alloc::{self};
-use std::cmp::{max, min};
-use std::fs::File;
-use std::io::Read;
-use std::mem::MaybeUninit;
-use std::os::fd::AsRawFd;
-use std::os::unix::fs::FileExt;
-use std::ptr::{null_mut, slice_from_raw_parts_mut};
-
-use crate::elf::dynamic_array::{DynamicArrayItem, DT_NEEDED, DT_NULL};
-use crate::elf::program_header::PT_LOAD;
+use core::{
+    cell::UnsafeCell,
+    ffi::{c_void, CStr},
+    slice,
+};
+use core::cmp::{max, min};
+use core::mem::MaybeUninit;
+use core::ptr::{null_mut, slice_from_raw_parts_mut};
+
+use alloc::{string::String, vec::Vec};
+
+use crate::elf::dynamic_array::{
+    DynamicArrayItem, DT_GNU_HASH, DT_HASH, DT_JMPREL, DT_NEEDED, DT_NULL, DT_PLTRELSZ,
+    DT_VERDEF, DT_VERDEFNUM, DT_VERNEED, DT_VERNEEDNUM, DT_VERSYM,
+};
+use crate::elf::hash_table::{GnuHashTable, HashTable, SysvHashTable};
+use crate::elf::program_header::{PT_GNU_EH_FRAME, PT_LOAD, PT_NOTE};
 use crate::elf::relocate::RelocationSlices;
 use crate::elf::symbol::SymbolTable;
+use crate::elf::symbol_version::SymbolVersions;
+use crate::exports::r_debug::LinkMap;
+use crate::statistics::{Counter, Statistics};
+use crate::trace;
+use crate::panic_handler;
+use crate::events;
+use crate::fs::File;
+use crate::error::LoaderError;
 use crate::linux::page_size;
 use crate::{
-    arch::{io, mmap, exit},
+    arch::{mmap, syscall::EEXIST},
     elf::{
         dynamic_array::{
-            DynamicArrayIter, DT_RELA, DT_RELAENT, DT_RELASZ, DT_STRTAB, DT_SYMENT, DT_SYMTAB,
+            DynamicArrayIter, DT_RELA, DT_RELAENT, DT_RELASZ, DT_RPATH, DT_RUNPATH, DT_SONAME,
+            DT_STRTAB, DT_SYMENT, DT_SYMTAB,
         },
-        header::{ElfHeader, ET_DYN},
+        header::{ElfHeader, ET_DYN, ET_EXEC},
         program_header::{ProgramHeader, PT_DYNAMIC, PT_PHDR, PT_TLS},
         relocate::Rela,
         string_table::StringTable,
@@ -55,55 +69,137 @@ fn calculate_virtual_address_bounds(program_header_table: &[ProgramHeader]) -> (
 
 /// A struct repersenting a shared object in memory.
 ///
-/// There are two ways to construct a `SharedObject`:
+/// There are three ways to construct a `SharedObject`:
 ///
 /// 1. From a slice of program headers:
 ///
 /// 2. From a file descriptor:
+///
+/// 3. From the base of an ELF image that is already mapped (like our own):
+///
+/// 4. From the vDSO the kernel mapped for us (`AT_SYSINFO_EHDR`):
+#[repr(C)]
 pub struct SharedObject {
+    // NOTE: This has to stay the first field; everyone outside of miros treats a `*const SharedObject` as a `*mut LinkMap`.
+    pub link_map: UnsafeCell<LinkMap>,
     pub base: *const (),
+    pub name: &'static CStr,
+    pub program_header_table: &'static [ProgramHeader],
     pub relocations: RelocationSlices,
     pub needed_libraries: Vec<usize>, // Indexs into the string table...
+    pub soname: Option<usize>,
+    pub rpath: Option<usize>,
+    pub runpath: Option<usize>,
     pub symbol_table: SymbolTable,
     pub string_table: StringTable,
-    pub thread_local_block: Option<usize>,
+    pub hash_table: Option<HashTable>,
+    pub symbol_versions: SymbolVersions,
+    pub thread_local_block: Option<usize>, // The TLS module id...
+    pub statistics: Statistics,
 }
 
 impl SharedObject {
     pub unsafe fn from_headers(
-        program_header_table: &[ProgramHeader],
+        program_header_table: &'static [ProgramHeader],
         pseudorandom_bytes: *const [u8; 16],
     ) -> Self {
-        let (mut base, mut dynamic_header, mut tls_program_header) = (null(), None, None);
+        let (mut base, mut dynamic_header) = (null(), None);
         for header in program_header_table {
             match header.p_type {
                 PT_PHDR => {
-                    base = program_header_table.as_ptr().byte_sub(header.p_vaddr) as *const ();
+                    base = program_header_table.as_ptr().wrapping_byte_sub(header.p_vaddr) as *const ();
                 }
                 PT_DYNAMIC => dynamic_header = Some(header),
-                PT_TLS => tls_program_header = Some(header),
                 _ => (),
             }
         }
         syscall_debug_assert!(dynamic_header.is_some());
-        syscall_debug_assert!(tls_program_header.is_some());
 
-        Self::build(base, dynamic_header.unwrap_unchecked(), Some(1))
+        // NOTE: The executable's name is always empty, that's what everyone iterating the loaded objects expects.
+        Self::build(
+            base,
+            c"",
+            program_header_table,
+            dynamic_header.unwrap_unchecked(),
+        )
+    }
+
+    pub unsafe fn from_base(base: *const (), name: &'static CStr) -> Self {
+        // ELf Header:
+        let header = &*(base as *const ElfHeader);
+        syscall_debug_assert!(header.e_type == ET_DYN);
+        syscall_debug_assert!(header.e_phentsize == size_of::<ProgramHeader>() as u16);
+
+        // Program Headers:
+        let program_header_table = slice::from_raw_parts(
+            base.byte_add(header.e_phoff) as *const ProgramHeader,
+            header.e_phnum as usize,
+        );
+
+        let mut dynamic_header = None;
+        for header in program_header_table {
+            if header.p_type == PT_DYNAMIC {
+                dynamic_header = Some(header);
+            }
+        }
+        syscall_debug_assert!(dynamic_header.is_some());
+
+        Self::build(
+            base,
+            name,
+            program_header_table,
+            dynamic_header.unwrap_unchecked(),
+        )
+    }
+
+    /// The vDSO is a complete shared object the kernel maps into every process, it doesn't come from a file.
+    pub unsafe fn from_vdso(header: *const ElfHeader) -> Self {
+        syscall_debug_assert!((*header).e_type == ET_DYN);
+        syscall_debug_assert!((*header).e_phentsize == size_of::<ProgramHeader>() as u16);
+
+        let program_header_table = slice::from_raw_parts(
+            header.byte_add((*header).e_phoff) as *const ProgramHeader,
+            (*header).e_phnum as usize,
+        );
+
+        // Unlike our own image it isn't necessarily linked at zero, the load bias is wherever the first segment ended up
+        // minus where it asked to be:
+        let mut base = header as *const ();
+        for program_header in program_header_table {
+            if program_header.p_type == PT_LOAD {
+                base = header.byte_sub(program_header.p_vaddr) as *const ();
+                break;
+            }
+        }
+
+        let mut dynamic_header = None;
+        for program_header in program_header_table {
+            if program_header.p_type == PT_DYNAMIC {
+                dynamic_header = Some(program_header);
+            }
+        }
+        syscall_debug_assert!(dynamic_header.is_some());
+
+        // NOTE: Like glibc, it goes by its `DT_SONAME`; there is no file behind it.
+        Self::build(
+            base,
+            c"linux-vdso.so.1",
+            program_header_table,
+            dynamic_header.unwrap_unchecked(),
+        )
     }
 
-    pub unsafe fn from_file(mut file: File) -> Self {
+    /// Reads the ELF header and program header table from the start of a file, without mapping anything.
+    /// `path` is only used to report errors, they are fatal.
+    pub unsafe fn read_headers(file: &File, path: &str) -> (ElfHeader, Vec<ProgramHeader>) {
         // ELf Header:
         let mut uninit_header: MaybeUninit<ElfHeader> = MaybeUninit::uninit();
         let as_bytes = slice::from_raw_parts_mut(
             uninit_header.as_mut_ptr() as *mut u8,
             size_of::<ElfHeader>(),
         );
-        if let Err(error) = file.read_exact(as_bytes) {
-            io::write(
-                io::STD_ERR,
-                "Error: could not read ElfHeader from file",
-            );
-            exit::exit(1);
+        if let Err(errno) = file.read_exact_at(as_bytes, 0) {
+            LoaderError::CannotRead { path, errno }.exit();
         }
         let header = uninit_header.assume_init();
 
@@ -114,35 +210,72 @@ impl SharedObject {
             program_header_table.as_mut_ptr() as *mut u8,
             header.e_phnum as usize * size_of::<ProgramHeader>(),
         );
-        if let Err(error) = file.read_exact_at(as_bytes, header.e_phoff as u64) {
-            io::write(
-                io::STD_ERR,
-                "Error: could not read &[ProgramHeader] from file",
-            );
-            exit::exit(1);
+        if let Err(errno) = file.read_exact_at(as_bytes, header.e_phoff as u64) {
+            LoaderError::CannotRead { path, errno }.exit();
         }
         program_header_table.set_len(header.e_phnum as usize);
         syscall_debug_assert!(program_header_table.iter().any(|h| h.p_type == PT_LOAD));
 
+        (header, program_header_table)
+    }
+
+    pub unsafe fn from_file(file: File, name: &'static CStr) -> Self {
+        let _span = trace::span(trace::CATEGORY_MAPPING, &name.to_string_lossy());
+        if let Ok(name) = name.to_str() {
+            panic_handler::set_current_object(name);
+        }
+        let (header, program_header_table) = Self::read_headers(&file, &name.to_string_lossy());
         let (min_addr, max_addr) = calculate_virtual_address_bounds(&program_header_table);
+        let map_error = |errno| -> ! {
+            LoaderError::CannotMap { path: &name.to_string_lossy(), errno }.exit()
+        };
 
-        let base = mmap::mmap(
-            null_mut(),
-            max_addr - min_addr,
-            mmap::PROT_EXEC | mmap::PROT_READ | mmap::PROT_WRITE,
-            mmap::MAP_PRIVATE | mmap::MAP_ANONYMOUS,
-            -1,
-            0,
-        ) as *const ();
+        // Reserve all the address space we need up front, so nothing else ends up between the segments:
+        let base = if header.e_type == ET_EXEC {
+            // Non-PIE executables have to go exactly where they were linked, so the base is zero.
+            // NOTE: The base is derived from the reservation (not `null()`) so the addresses we build from it stay valid pointers.
+            match mmap::mmap(
+                min_addr as *mut u8,
+                max_addr - min_addr,
+                mmap::PROT_EXEC | mmap::PROT_READ | mmap::PROT_WRITE,
+                mmap::MAP_PRIVATE | mmap::MAP_ANONYMOUS | mmap::MAP_FIXED_NOREPLACE,
+                -1,
+                0,
+            ) {
+                // NOTE: Kernels older than 4.17 don't know `MAP_FIXED_NOREPLACE` and treat the address as a hint instead.
+                Ok(reservation) if reservation.addr() == min_addr => {
+                    reservation.wrapping_byte_sub(min_addr) as *const ()
+                }
+                Ok(_) | Err(EEXIST) => LoaderError::AddressConflict {
+                    path: &name.to_string_lossy(),
+                    start: min_addr,
+                    end: max_addr,
+                    miros: None,
+                }
+                .exit(),
+                Err(errno) => map_error(errno),
+            }
+        } else {
+            match mmap::mmap(
+                null_mut(),
+                max_addr - min_addr,
+                mmap::PROT_EXEC | mmap::PROT_READ | mmap::PROT_WRITE,
+                mmap::MAP_PRIVATE | mmap::MAP_ANONYMOUS,
+                -1,
+                0,
+            ) {
+                Ok(reservation) => reservation.byte_sub(min_addr) as *const (),
+                Err(errno) => map_error(errno),
+            }
+        };
 
-        let (mut dynamic_header, mut tls_program_header) = (None, None);
+        let mut dynamic_header = None;
         for header in &program_header_table {
             match header.p_type {
                 PT_DYNAMIC => dynamic_header = Some(header),
-                PT_TLS => tls_program_header = Some(header),
                 PT_LOAD => {
                     let segment_start =
-                        page_size::get_page_start(base.byte_add(header.p_vaddr) as usize);
+                        page_size::get_page_start(base.wrapping_byte_add(header.p_vaddr) as usize);
 
                     let file_start = page_size::get_page_start(header.p_offset);
                     let file_length = (header.p_offset + header.p_filesz) - file_start;
@@ -155,18 +288,20 @@ impl SharedObject {
                         | ((header.p_flags & ELF_FLAG_READ != 0) as usize * mmap::PROT_READ)
                         | ((header.p_flags & ELF_FLAG_WRITE != 0) as usize * mmap::PROT_WRITE);
 
-                    mmap::mmap(
+                    if let Err(errno) = mmap::mmap(
                         segment_start as *mut u8,
                         file_length,
                         flags,
                         mmap::MAP_PRIVATE | mmap::MAP_FIXED,
                         file.as_raw_fd() as isize,
                         file_start,
-                    );
+                    ) {
+                        map_error(errno);
+                    }
 
                     if header.p_memsz > header.p_filesz {
                         slice::from_raw_parts_mut(
-                            base.byte_add(header.p_vaddr).byte_add(header.p_filesz) as *mut u8,
+                            base.wrapping_byte_add(header.p_vaddr).wrapping_byte_add(header.p_filesz) as *mut u8,
                             header.p_memsz - header.p_filesz as usize,
                         )
                         .fill(0);
@@ -176,41 +311,173 @@ impl SharedObject {
             }
         }
 
-        syscall_debug_assert!(header == *(base as *const ElfHeader));
+        // The first segment starts at the beginning of the file, so the headers are mapped too:
+        let in_memory_header = base.wrapping_byte_add(min_addr);
+        syscall_debug_assert!(header == *(in_memory_header as *const ElfHeader));
 
         let in_memory_program_header_table = slice::from_raw_parts(
-            base.byte_add(header.e_phoff) as *const ProgramHeader,
+            in_memory_header.wrapping_byte_add(header.e_phoff) as *const ProgramHeader,
             header.e_phnum as usize,
         );
         syscall_debug_assert!(in_memory_program_header_table == program_header_table.as_slice());
 
-        let thread_local_block = tls_program_header.map(|header| 1); // TODO: tls
-
         syscall_debug_assert!(dynamic_header.is_some());
-        Self::build(base, dynamic_header.unwrap(), thread_local_block)
+        let shared_object = Self::build(
+            base,
+            name,
+            in_memory_program_header_table,
+            dynamic_header.unwrap(),
+        );
+        shared_object
+            .statistics
+            .add(Counter::BytesMapped, max_addr - min_addr);
+
+        events::object_mapped(&shared_object);
+        shared_object
+    }
+
+    /// The names of the libraries this object depends on (`DT_NEEDED`).
+    pub fn needed_library_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.needed_libraries
+            .iter()
+            .map(|&index| unsafe { self.string_table.get(index) })
+    }
+
+    /// The name this object wants to be known by (`DT_SONAME`).
+    pub fn soname(&self) -> Option<&'static str> {
+        self.soname.map(|index| unsafe { self.string_table.get(index) })
+    }
+
+    /// The name of the symbol at `symbol_index` in the dynamic symbol table.
+    pub unsafe fn symbol_name(&self, symbol_index: usize) -> &'static str {
+        self.string_table
+            .get(self.symbol_table.get(symbol_index).st_name as usize)
+    }
+
+    /// The path this object was loaded from, empty for the executable.
+    pub fn path(&self) -> &'static str {
+        self.name.to_str().unwrap_or("")
+    }
+
+    /// The name glibc reports this object under in diagnostics, the executable doesn't have a path of its own.
+    pub fn display_name(&self) -> &'static str {
+        match self.path() {
+            "" => "<main program>",
+            path => path,
+        }
+    }
+
+    /// The unique ID the linker gave this build (`NT_GNU_BUILD_ID`), if it has one.
+    ///
+    /// Notes are laid out like this, with the name and descriptor padded to 4 bytes:
+    ///
+    /// ```no_run
+    /// |------------------------------|
+    /// | n_namesz: u32                |
+    /// | n_descsz: u32                |
+    /// | n_type: u32                  |
+    /// |------------------------------|
+    /// | name: [u8; n_namesz]         | <- "GNU\0"
+    /// |------------------------------|
+    /// | descriptor: [u8; n_descsz]   | <- the build ID
+    /// |------------------------------|
+    /// ```
+    pub unsafe fn build_id(&self) -> Option<&'static [u8]> {
+        const NT_GNU_BUILD_ID: u32 = 3;
+
+        for header in self.program_header_table {
+            if header.p_type != PT_NOTE {
+                continue;
+            }
+            let mut note = self.base.wrapping_byte_add(header.p_vaddr) as *const u8;
+            let end = note.add(header.p_memsz);
+            while note.add(12) <= end {
+                let (name_size, descriptor_size, note_type) = (
+                    *(note as *const u32) as usize,
+                    *(note.add(4) as *const u32) as usize,
+                    *(note.add(8) as *const u32),
+                );
+                let name = slice::from_raw_parts(note.add(12), name_size);
+                let descriptor = note.add(12 + name_size.next_multiple_of(4));
+                if note_type == NT_GNU_BUILD_ID && name == b"GNU\0" {
+                    return Some(slice::from_raw_parts(descriptor, descriptor_size));
+                }
+                note = descriptor.add(descriptor_size.next_multiple_of(4));
+            }
+        }
+        None
+    }
+
+    /// The build ID as a hex string, the way `file` and debuggers show it.
+    pub unsafe fn build_id_hex(&self) -> Option<String> {
+        let build_id = self.build_id()?;
+        Some(build_id.iter().map(|byte| format!("{byte:02x}")).collect())
+    }
+
+    /// The page aligned `(start, end)` addresses covered by the loaded segments.
+    pub fn mapped_bounds(&self) -> (usize, usize) {
+        Self::bounds_of(self.base, self.program_header_table)
+    }
+
+    pub fn bounds_of(base: *const (), program_header_table: &[ProgramHeader]) -> (usize, usize) {
+        let (min_addr, max_addr) = calculate_virtual_address_bounds(program_header_table);
+        (base.addr() + min_addr, base.addr() + max_addr)
+    }
+
+    /// Whether the object has a `PT_TLS` segment, i.e. needs a thread local storage module of its own.
+    pub fn has_thread_local_storage(&self) -> bool {
+        self.program_header_table
+            .iter()
+            .any(|header| header.p_type == PT_TLS)
+    }
+
+    /// The address of the `.eh_frame_hdr` section unwinders search, null if there isn't one.
+    pub fn eh_frame_header(&self) -> *const () {
+        Self::eh_frame_header_of(self.base, self.program_header_table)
+    }
+
+    pub fn eh_frame_header_of(
+        base: *const (),
+        program_header_table: &[ProgramHeader],
+    ) -> *const () {
+        for header in program_header_table {
+            if header.p_type == PT_GNU_EH_FRAME {
+                return base.wrapping_byte_add(header.p_vaddr);
+            }
+        }
+        null()
     }
 
     unsafe fn build(
         base: *const (),
+        name: &'static CStr,
+        program_header_table: &'static [ProgramHeader],
         dynamic_header: &ProgramHeader,
-        thread_local_block: Option<usize>,
     ) -> Self {
         // Dynamic Arrary:
         let dynamic_array =
-            DynamicArrayIter::new(base.byte_add(dynamic_header.p_vaddr) as *const DynamicArrayItem);
+            DynamicArrayIter::new(base.wrapping_byte_add(dynamic_header.p_vaddr) as *const DynamicArrayItem);
         syscall_debug_assert!(dynamic_array.clone().count() != 0);
 
         let mut rela_pointer: *const Rela = null();
         let mut rela_count = 0;
+        let mut plt_rela_pointer: *const Rela = null();
+        let mut plt_rela_count = 0;
 
+        let (mut gnu_hash_pointer, mut sysv_hash_pointer): (*const u32, *const u32) = (null(), null());
+        let mut symbol_versions = SymbolVersions::none();
         let mut symbol_table_pointer: *const Symbol = null();
         let mut string_table_pointer: *const u8 = null();
         let mut needed_libraries = Vec::new();
+        let (mut soname, mut rpath, mut runpath) = (None, None, None);
         for item in dynamic_array {
             match item.d_tag {
                 DT_NEEDED => needed_libraries.push(item.d_un.d_val),
+                DT_SONAME => soname = Some(item.d_un.d_val),
+                DT_RPATH => rpath = Some(item.d_un.d_val),
+                DT_RUNPATH => runpath = Some(item.d_un.d_val),
                 DT_RELA => {
-                    rela_pointer = base.byte_add(item.d_un.d_ptr.addr()) as *const Rela;
+                    rela_pointer = base.wrapping_byte_add(item.d_un.d_ptr.addr()) as *const Rela;
                 }
                 DT_RELASZ => {
                     rela_count = item.d_un.d_val / core::mem::size_of::<Rela>();
@@ -219,12 +486,36 @@ impl SharedObject {
                 DT_RELAENT => {
                     syscall_assert!(item.d_un.d_val as usize == size_of::<Rela>())
                 }
+                // NOTE: x86_64 only uses `Rela` for PLT relocations, so we don't need to check `DT_PLTREL`.
+                DT_JMPREL => {
+                    plt_rela_pointer = base.wrapping_byte_add(item.d_un.d_ptr.addr()) as *const Rela;
+                }
+                DT_PLTRELSZ => {
+                    plt_rela_count = item.d_un.d_val / core::mem::size_of::<Rela>();
+                }
+                // Hash Tables:
+                DT_GNU_HASH => {
+                    gnu_hash_pointer = base.wrapping_byte_add(item.d_un.d_ptr.addr()) as *const u32
+                }
+                DT_HASH => sysv_hash_pointer = base.wrapping_byte_add(item.d_un.d_ptr.addr()) as *const u32,
+                // Symbol Versioning:
+                DT_VERSYM => {
+                    symbol_versions.versym = base.wrapping_byte_add(item.d_un.d_ptr.addr()) as *const u16
+                }
+                DT_VERDEF => {
+                    symbol_versions.verdef = base.wrapping_byte_add(item.d_un.d_ptr.addr()).cast()
+                }
+                DT_VERDEFNUM => symbol_versions.verdef_count = item.d_un.d_val,
+                DT_VERNEED => {
+                    symbol_versions.verneed = base.wrapping_byte_add(item.d_un.d_ptr.addr()).cast()
+                }
+                DT_VERNEEDNUM => symbol_versions.verneed_count = item.d_un.d_val,
                 // Tables:
                 DT_SYMTAB => {
-                    symbol_table_pointer = base.byte_add(item.d_un.d_ptr.addr()) as *const Symbol
+                    symbol_table_pointer = base.wrapping_byte_add(item.d_un.d_ptr.addr()) as *const Symbol
                 }
                 DT_STRTAB => {
-                    string_table_pointer = base.byte_add(item.d_un.d_ptr.addr()) as *const u8
+                    string_table_pointer = base.wrapping_byte_add(item.d_un.d_ptr.addr()) as *const u8
                 }
                 #[cfg(debug_assertions)]
                 DT_SYMENT => {
@@ -234,16 +525,49 @@ impl SharedObject {
             }
         }
 
-        syscall_debug_assert!(rela_pointer != null());
-        let rela_slice = slice::from_raw_parts(rela_pointer, rela_count);
+        // NOTE: Not everything has relocations, plenty of small libraries only have `DT_JMPREL`.
+        let rela_slice = match rela_pointer.is_null() {
+            true => &[],
+            false => slice::from_raw_parts(rela_pointer, rela_count),
+        };
+        let plt_rela_slice = match plt_rela_pointer.is_null() {
+            true => &[],
+            false => slice::from_raw_parts(plt_rela_pointer, plt_rela_count),
+        };
+
+        // NOTE: Most objects only have the GNU hash table these days, it's also the faster one.
+        let hash_table = if !gnu_hash_pointer.is_null() {
+            Some(HashTable::Gnu(GnuHashTable::new(gnu_hash_pointer)))
+        } else if !sysv_hash_pointer.is_null() {
+            Some(HashTable::Sysv(SysvHashTable::new(sysv_hash_pointer)))
+        } else {
+            None
+        };
 
         Self {
+            link_map: UnsafeCell::new(LinkMap::new(
+                base,
+                name.as_ptr(),
+                base.wrapping_byte_add(dynamic_header.p_vaddr) as *mut DynamicArrayItem,
+            )),
             base,
-            relocations: RelocationSlices { rela_slice },
+            name,
+            program_header_table,
+            relocations: RelocationSlices {
+                rela_slice,
+                plt_rela_slice,
+            },
             needed_libraries,
+            soname,
+            rpath,
+            runpath,
             symbol_table: SymbolTable::new(symbol_table_pointer),
             string_table: StringTable::new(string_table_pointer),
-            thread_local_block,
+            hash_table,
+            symbol_versions,
+            // Objects only get a module id once they are registered, see `loaded_objects::add`.
+            thread_local_block: None,
+            statistics: Statistics::new(),
         }
     }
 }
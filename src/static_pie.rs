@@ -1,4 +1,4 @@
-use std::{
+use core::{
     cmp::max,
     marker::PhantomData,
     ptr::{null, null_mut},
@@ -20,6 +20,7 @@ use crate::{
         symbol::Symbol,
         thread_local_storage::ThreadControlBlock,
     },
+    error::LoaderError,
     syscall_debug_assert,
 };
 
@@ -156,7 +157,10 @@ impl StaticPie<Ingredients> {
 
         StaticPie::<Ingredients> {
             base_address: base,
-            relocation_slices: RelocationSlices { rela_slice },
+            relocation_slices: RelocationSlices {
+                rela_slice,
+                plt_rela_slice: &[],
+            },
             tls_program_header,
             pseudorandom_bytes,
             phantom_data: PhantomData,
@@ -192,9 +196,12 @@ impl StaticPie<Baked> {
         // |   ┌------------------┐
         // └-> | Null Dtv Pointer |
         //     └------------------┘
-        let Some(tls_program_header) = self.tls_program_header else {
-            return;
-        };
+        // Even without thread locals of our own there has to be a TCB, the `LoaderLock` tells threads apart by it:
+        let tls_program_header = self.tls_program_header.copied().unwrap_or(ProgramHeader {
+            p_type: PT_TLS,
+            p_align: 1,
+            ..ProgramHeader::default()
+        });
 
         let tcb_and_tls_align = max(tls_program_header.p_align, align_of::<ThreadControlBlock>());
         let tls_blocks_size_and_align =
@@ -202,14 +209,17 @@ impl StaticPie<Baked> {
         let tcb_size = size_of::<ThreadControlBlock>();
 
         let required_size = tcb_and_tls_align + tls_blocks_size_and_align + tcb_size;
-        let tls_allocation_pointer = mmap(
+        let tls_allocation_pointer = match mmap(
             null_mut(),
             required_size,
             PROT_READ | PROT_WRITE,
             MAP_PRIVATE | MAP_ANONYMOUS,
             -1, // file descriptor (-1 for anonymous mapping)
             0,  // offset
-        );
+        ) {
+            Ok(pointer) => pointer,
+            Err(errno) => LoaderError::CannotAllocateTls { errno }.exit(),
+        };
         syscall_debug_assert!(tls_allocation_pointer.addr() % tcb_and_tls_align == 0);
 
         let tls_block_pointer = tls_allocation_pointer.byte_add(tcb_and_tls_align);
@@ -244,7 +254,7 @@ impl StaticPie<Baked> {
             dynamic_thread_vector: null_mut(),
             _padding: [0; 3],
             canary: usize::from_ne_bytes(
-                (*self.pseudorandom_bytes)[..size_of::<usize>()]
+                (&*self.pseudorandom_bytes)[..size_of::<usize>()]
                     .try_into()
                     .unwrap(),
             ),
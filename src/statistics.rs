@@ -0,0 +1,174 @@
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::{
+    arch::time::{clock_gettime, CLOCK_MONOTONIC},
+    debug::{self, DEBUG_STATISTICS},
+    loaded_objects,
+    loader_lock::LOADER_LOCK,
+};
+
+/// The things we count, both per object and in total.
+#[derive(Clone, Copy)]
+pub enum Counter {
+    RelativeRelocations,
+    SymbolicRelocations,
+    /// Objects searched for a symbol.
+    SymbolLookups,
+    /// Lookups the GNU hash table's bloom filter answered without touching the buckets.
+    BloomRejections,
+    BytesMapped,
+}
+
+const COUNTERS: [(Counter, &str); 5] = [
+    (Counter::RelativeRelocations, "relative relocations"),
+    (Counter::SymbolicRelocations, "symbolic relocations"),
+    (Counter::SymbolLookups, "symbol lookups"),
+    (Counter::BloomRejections, "bloom filter rejections"),
+    (Counter::BytesMapped, "bytes mapped"),
+];
+
+/// A set of counters, every `SharedObject` has one and so do the totals.
+pub struct Statistics([AtomicUsize; COUNTERS.len()]);
+
+impl Statistics {
+    pub const fn new() -> Self {
+        Self([const { AtomicUsize::new(0) }; COUNTERS.len()])
+    }
+
+    /// Adds `amount` to this object's `counter`, and to the total.
+    pub fn add(&self, counter: Counter, amount: usize) {
+        self.0[counter as usize].fetch_add(amount, Ordering::Relaxed);
+        TOTALS.0[counter as usize].fetch_add(amount, Ordering::Relaxed);
+    }
+
+    pub fn get(&self, counter: Counter) -> usize {
+        self.0[counter as usize].load(Ordering::Relaxed)
+    }
+}
+
+static TOTALS: Statistics = Statistics::new();
+/// Not tied to any object, most files we open while searching are the wrong ones.
+static FILES_OPENED: AtomicUsize = AtomicUsize::new(0);
+
+pub fn count_file_opened() {
+    FILES_OPENED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The steps of startup, in the order they happen.
+#[derive(Clone, Copy)]
+pub enum Phase {
+    Start,
+    SelfRelocated,
+    ExecutableLoaded,
+    LibrariesLoaded,
+    SymbolsResolved,
+    Done,
+}
+
+const PHASES: [(Phase, &str); 6] = [
+    (Phase::Start, "start"),
+    (Phase::SelfRelocated, "relocating ourselves"),
+    (Phase::ExecutableLoaded, "loading the executable"),
+    (Phase::LibrariesLoaded, "loading libraries"),
+    (Phase::SymbolsResolved, "resolving symbols"),
+    (Phase::Done, "finishing up"),
+];
+
+/// When each phase finished, in monotonic nanoseconds; zero if it never happened.
+static TIMESTAMPS: [AtomicU64; PHASES.len()] = [const { AtomicU64::new(0) }; PHASES.len()];
+
+/// The monotonic clock, in nanoseconds.
+#[inline(always)]
+pub fn now() -> u64 {
+    let time = clock_gettime(CLOCK_MONOTONIC).unwrap_or_default();
+    time.tv_sec as u64 * 1_000_000_000 + time.tv_nsec as u64
+}
+
+/// Records that `phase` just finished.
+///
+/// NOTE: This only uses a system call and atomics, so it's fine to call before we are relocated.
+#[inline(always)]
+pub fn mark(phase: Phase) {
+    TIMESTAMPS[phase as usize].store(now(), Ordering::Relaxed);
+}
+
+/// The phases that happened so far, with when they started and ended.
+pub fn phases() -> Vec<(&'static str, u64, u64)> {
+    let mut phases = Vec::new();
+    let mut previous = TIMESTAMPS[Phase::Start as usize].load(Ordering::Relaxed);
+    for (phase, description) in &PHASES[1..] {
+        let timestamp = TIMESTAMPS[*phase as usize].load(Ordering::Relaxed);
+        if timestamp == 0 {
+            continue;
+        }
+        phases.push((*description, previous, timestamp));
+        previous = timestamp;
+    }
+    phases
+}
+
+/// What we are in the middle of, the phase that hasn't finished yet.
+pub fn current_phase() -> &'static str {
+    for (phase, description) in &PHASES[1..] {
+        if TIMESTAMPS[*phase as usize].load(Ordering::Relaxed) == 0 {
+            return description;
+        }
+    }
+    PHASES[PHASES.len() - 1].1
+}
+
+/// Prints the timing and counters (`LD_DEBUG=statistics` or `--stats`), after `Phase::Done` was marked:
+///
+/// ```text
+/// runtime linker statistics:
+///   total startup time in dynamic loader: 1532871 ns
+///                   relocating ourselves: 41205 ns (2.6%)
+///   ...
+///   /lib/x86_64-linux-gnu/libc.so.6:
+///                   symbolic relocations: 1567
+/// ```
+pub fn report() {
+    if !debug::is_enabled(DEBUG_STATISTICS) {
+        return;
+    }
+
+    let start = TIMESTAMPS[Phase::Start as usize].load(Ordering::Relaxed);
+    let total = TIMESTAMPS[Phase::Done as usize]
+        .load(Ordering::Relaxed)
+        .saturating_sub(start)
+        .max(1);
+    debug::write_line(&[""]);
+    debug::write_line(&["runtime linker statistics:"]);
+    debug::write_line(&[&format!(
+        "  total startup time in dynamic loader: {total} ns"
+    )]);
+
+    // Each phase took from the end of the last one that happened:
+    for (description, start, end) in phases() {
+        let duration = end.saturating_sub(start);
+        let percent = duration as f64 * 100.0 / total as f64;
+        debug::write_line(&[&format!("{description:>38}: {duration} ns ({percent:.1}%)")]);
+    }
+
+    debug::write_line(&[&format!(
+        "{:>38}: {}",
+        "files opened",
+        FILES_OPENED.load(Ordering::Relaxed)
+    )]);
+    print_counters(&TOTALS);
+
+    let guard = LOADER_LOCK.lock();
+    let mut index = 0;
+    while let Some(shared_object) = loaded_objects::get(&guard, index) {
+        index += 1;
+        debug::write_line(&["  ", shared_object.display_name(), ":"]);
+        print_counters(&shared_object.statistics);
+    }
+}
+
+fn print_counters(statistics: &Statistics) {
+    for (counter, description) in COUNTERS {
+        debug::write_line(&[&format!("{description:>38}: {}", statistics.get(counter))]);
+    }
+}
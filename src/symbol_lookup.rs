@@ -0,0 +1,243 @@
+use alloc::vec::Vec;
+
+use crate::{
+    arch::relocation::{R_X86_64_COPY, R_X86_64_RELATIVE},
+    debug::{debug_println, DEBUG_BINDINGS, DEBUG_RELOC, DEBUG_SYMBOLS},
+    elf::{
+        hash_table::HashCandidates,
+        relocate::Rela,
+        symbol::{Symbol, SHN_UNDEF, STB_LOCAL, STB_WEAK, STV_PROTECTED},
+        symbol_version::RequiredVersion,
+    },
+    events::{self, Value},
+    loaded_objects,
+    loader_lock::LOADER_LOCK,
+    panic_handler,
+    shared_object::SharedObject,
+    statistics::Counter,
+    trace,
+};
+
+/// Where a symbol reference ended up.
+#[derive(Clone, Copy)]
+pub struct Definition {
+    pub shared_object: &'static SharedObject,
+    pub symbol_index: usize,
+    pub symbol: Symbol,
+}
+
+/// A symbolic relocation, and the definition it binds to.
+pub struct Binding {
+    pub rela: &'static Rela,
+    pub name: &'static str,
+    pub version: Option<RequiredVersion>,
+    /// Weak references are allowed to stay unresolved, they just end up as null.
+    pub weak: bool,
+    pub definition: Option<Definition>,
+}
+
+/// Every loaded object in the order glibc relocates them: dependencies first, the executable last.
+///
+/// We (the interpreter) are left out, we relocated ourselves long ago.
+pub fn relocation_order(interpreter: &SharedObject) -> Vec<&'static SharedObject> {
+    let guard = LOADER_LOCK.lock();
+    let mut objects = Vec::new();
+    let mut index = 0;
+    while let Some(shared_object) = loaded_objects::get(&guard, index) {
+        index += 1;
+        if !core::ptr::eq(shared_object, interpreter) {
+            objects.push(shared_object);
+        }
+    }
+    objects.reverse();
+    objects
+}
+
+/// Reports that we are moving on to `shared_object`'s relocations, the span lasts until the returned value is dropped.
+pub fn start_relocating(shared_object: &SharedObject) -> trace::Span {
+    debug_println!(DEBUG_RELOC, "");
+    debug_println!(
+        DEBUG_RELOC,
+        "relocation processing: ",
+        shared_object.display_name()
+    );
+    panic_handler::set_current_object(shared_object.display_name());
+    trace::span(trace::CATEGORY_RELOCATION, shared_object.display_name())
+}
+
+/// Resolves every symbolic relocation of every object (but us), in relocation order.
+///
+/// NOTE: Nothing is applied yet, but the bindings are still reported (`LD_DEBUG=bindings`, `symbol_bound` events) and
+/// counted (`LD_DEBUG=statistics`) like they would be on a real run.
+pub unsafe fn resolve_all(interpreter: &SharedObject) {
+    for shared_object in relocation_order(interpreter) {
+        let _span = start_relocating(shared_object);
+        for rela in shared_object.relocations.iter() {
+            resolve(shared_object, rela);
+        }
+    }
+}
+
+/// Resolves the symbol `rela` in `shared_object` refers to, `None` if it doesn't refer to one (like `R_X86_64_RELATIVE`).
+pub unsafe fn resolve(
+    shared_object: &'static SharedObject,
+    rela: &'static Rela,
+) -> Option<Binding> {
+    let symbol_index = rela.r_sym() as usize;
+    if symbol_index == 0 {
+        if rela.r_type() == R_X86_64_RELATIVE {
+            shared_object
+                .statistics
+                .add(Counter::RelativeRelocations, 1);
+        }
+        return None;
+    }
+
+    shared_object
+        .statistics
+        .add(Counter::SymbolicRelocations, 1);
+    let symbol = shared_object.symbol_table.get(symbol_index);
+    let name = shared_object.symbol_name(symbol_index);
+    let version = shared_object
+        .symbol_versions
+        .required(symbol_index, &shared_object.string_table);
+
+    let definition = if symbol.st_bind() == STB_LOCAL || is_protected_definition(rela, &symbol) {
+        // Local and protected symbols can't be interposed, they always refer to the object itself.
+        Some(Definition {
+            shared_object,
+            symbol_index,
+            symbol,
+        })
+    } else {
+        // The executable's copy would just find itself:
+        let skip = (rela.r_type() == R_X86_64_COPY).then_some(shared_object);
+        lookup(name, version.as_ref(), skip)
+    };
+
+    if let Some(definition) = &definition {
+        events::emit(
+            "symbol_bound",
+            &[
+                ("symbol", Value::String(name)),
+                (
+                    "version",
+                    events::optional(version.map(|version| version.name)),
+                ),
+                ("from", Value::String(shared_object.display_name())),
+                ("to", Value::String(definition.shared_object.display_name())),
+            ],
+        );
+        let version_name = version.map_or("", |version| version.name);
+        debug_println!(
+            DEBUG_BINDINGS,
+            "binding file ",
+            shared_object.display_name(),
+            " [0] to ",
+            definition.shared_object.display_name(),
+            " [0]: normal symbol `",
+            name,
+            "'",
+            if version.is_some() { " [" } else { "" },
+            version_name,
+            if version.is_some() { "]" } else { "" },
+        );
+    }
+
+    Some(Binding {
+        rela,
+        name,
+        version,
+        weak: symbol.st_bind() == STB_WEAK,
+        definition,
+    })
+}
+
+/// Whether `symbol` is the referencing object's own protected definition, which its references bind to no matter what
+/// comes first in the scope.
+///
+/// NOTE: Copy relocations still need the original definition, the object's own "definition" is the copy.
+pub fn is_protected_definition(rela: &Rela, symbol: &Symbol) -> bool {
+    symbol.st_visibility() == STV_PROTECTED
+        && symbol.st_shndx != SHN_UNDEF
+        && rela.r_type() != R_X86_64_COPY
+}
+
+/// Looks `name` up in the global scope: every loaded object, in load order, the first definition wins.
+///
+/// `skip` is left out of the search, copy relocations need the definition the executable's copy came from.
+/// NOTE: Like glibc (without `LD_DYNAMIC_WEAK`), a weak definition is just as good as a global one.
+pub unsafe fn lookup(
+    name: &str,
+    version: Option<&RequiredVersion>,
+    skip: Option<&SharedObject>,
+) -> Option<Definition> {
+    let guard = LOADER_LOCK.lock();
+
+    let mut index = 0;
+    while let Some(shared_object) = loaded_objects::get(&guard, index) {
+        index += 1;
+        if skip.is_some_and(|skip| core::ptr::eq(skip, shared_object)) {
+            continue;
+        }
+        debug_println!(
+            DEBUG_SYMBOLS,
+            "symbol=",
+            name,
+            ";  lookup in file=",
+            shared_object.display_name(),
+            " [0]"
+        );
+        if let Some((symbol_index, symbol)) = find_in(shared_object, name, version) {
+            return Some(Definition {
+                shared_object,
+                symbol_index,
+                symbol,
+            });
+        }
+    }
+    None
+}
+
+/// Looks `name` up in `shared_object` alone, returning the symbol and its index.
+pub unsafe fn find_in(
+    shared_object: &SharedObject,
+    name: &str,
+    version: Option<&RequiredVersion>,
+) -> Option<(usize, Symbol)> {
+    let hash_table = shared_object.hash_table.as_ref()?;
+
+    shared_object.statistics.add(Counter::SymbolLookups, 1);
+    let candidates = hash_table.candidates(name);
+    if let HashCandidates::Rejected = candidates {
+        shared_object.statistics.add(Counter::BloomRejections, 1);
+        return None;
+    }
+    for symbol_index in candidates {
+        let symbol = shared_object.symbol_table.get(symbol_index);
+        if symbol.is_exported_definition()
+            && shared_object.symbol_name(symbol_index) == name
+            && version_matches(shared_object, symbol_index, version)
+        {
+            return Some((symbol_index, symbol));
+        }
+    }
+    None
+}
+
+/// Whether the definition at `symbol_index` satisfies a reference needing `required`, see `DefinedVersion::satisfies`.
+pub unsafe fn version_matches(
+    shared_object: &SharedObject,
+    symbol_index: usize,
+    required: Option<&RequiredVersion>,
+) -> bool {
+    let Some(defined) = shared_object
+        .symbol_versions
+        .defined(symbol_index, &shared_object.string_table)
+    else {
+        // Objects without versioning satisfy every version.
+        return true;
+    };
+
+    defined.satisfies(required)
+}
@@ -0,0 +1,108 @@
+use core::{
+    cell::UnsafeCell,
+    hint::spin_loop,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+};
+
+/// A value that is set at most once, like `std::sync::OnceLock` but without `std`.
+///
+/// NOTE: Only `set` and `get`, nothing blocks: whoever loses the race to set it just gets its value back.
+pub struct OnceLock<T> {
+    state: AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+// Once set the value is only read, and setting it is guarded by `state`.
+unsafe impl<T: Sync + Send> Sync for OnceLock<T> {}
+
+impl<T> OnceLock<T> {
+    const EMPTY: u8 = 0;
+    const SETTING: u8 = 1;
+    const SET: u8 = 2;
+
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(Self::EMPTY),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        if self.state.load(Ordering::Acquire) != Self::SET {
+            return None;
+        }
+        Some(unsafe { (*self.value.get()).assume_init_ref() })
+    }
+
+    /// Sets the value if it wasn't already, otherwise hands `value` back.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self
+            .state
+            .compare_exchange(
+                Self::EMPTY,
+                Self::SETTING,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            return Err(value);
+        }
+        unsafe { (*self.value.get()).write(value) };
+        self.state.store(Self::SET, Ordering::Release);
+        Ok(())
+    }
+}
+
+/// A spin lock around a value, for the few places that need one outside the `LoaderLock`.
+pub struct Mutex<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// The value is only reachable through a guard, and there is only ever one.
+unsafe impl<T: Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            spin_loop();
+        }
+        MutexGuard(self)
+    }
+}
+
+pub struct MutexGuard<'a, T>(&'a Mutex<T>);
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.0.value.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.0.value.get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.0.locked.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,200 @@
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{arch::io, linux::environment_variables::EnvironmentIter};
+
+/// Set when `NO_COLOR` asks us not to style anything, see <https://no-color.org>.
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Reads `NO_COLOR`, until then output to a terminal is styled.
+pub fn initialize(environment: EnvironmentIter) {
+    for (name, value) in environment {
+        if name == "NO_COLOR" && !value.is_empty() {
+            NO_COLOR.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Whether what goes to `fd` should keep the escape sequences `bold!` and `underline!` add.
+fn is_styled(fd: i32) -> bool {
+    !NO_COLOR.load(Ordering::Relaxed) && io::is_terminal(fd)
+}
+
+/// A buffer on the stack in front of a file descriptor, it's written out when full, flushed or dropped.
+///
+/// Anything can be formatted into it without the allocator, and through `syscall_write!` without `core::fmt` too, which
+/// makes it usable before we are relocated and have thread local storage (`core::fmt` calls through function pointers
+/// and trait objects, both need relocating).
+///
+/// Styling (`\x1B[...m`) is left out unless `fd` is a terminal and `NO_COLOR` isn't set.
+pub struct Writer {
+    fd: i32,
+    buffer: [u8; 256],
+    length: usize,
+    /// Unknown until the first escape sequence shows up, most output has none.
+    styled: Option<bool>,
+    in_escape: bool,
+}
+
+impl Writer {
+    pub const fn new(fd: i32) -> Self {
+        Self {
+            fd,
+            buffer: [0; 256],
+            length: 0,
+            styled: None,
+            in_escape: false,
+        }
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if byte == 0x1B && !self.in_escape {
+                self.in_escape = !*self.styled.get_or_insert_with(|| is_styled(self.fd));
+            }
+            if self.in_escape {
+                // Escape sequences we drop all end with a letter, ours with `m`:
+                self.in_escape = !byte.is_ascii_alphabetic();
+                continue;
+            }
+            if self.length == self.buffer.len() {
+                self.flush();
+            }
+            self.buffer[self.length] = byte;
+            self.length += 1;
+        }
+    }
+
+    pub fn flush(&mut self) {
+        // SAFETY: The buffer can end in the middle of a character, the kernel only sees bytes anyway.
+        io::write(self.fd, unsafe {
+            core::str::from_utf8_unchecked(&self.buffer[..self.length])
+        });
+        self.length = 0;
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        if self.length != 0 {
+            self.flush();
+        }
+    }
+}
+
+impl fmt::Write for Writer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// What `syscall_write!` can print: strings, integers in decimal, `Hex` and pointers.
+///
+/// NOTE: Called directly rather than through a trait object, so it works before we are relocated.
+pub trait SyscallDisplay {
+    fn write_to(&self, writer: &mut Writer);
+}
+
+impl SyscallDisplay for str {
+    fn write_to(&self, writer: &mut Writer) {
+        writer.write_bytes(self.as_bytes());
+    }
+}
+
+impl<T: SyscallDisplay + ?Sized> SyscallDisplay for &T {
+    fn write_to(&self, writer: &mut Writer) {
+        (**self).write_to(writer);
+    }
+}
+
+macro_rules! impl_unsigned {
+    ($($type:ty),+) => {
+        $(
+            impl SyscallDisplay for $type {
+                fn write_to(&self, writer: &mut Writer) {
+                    let mut buffer = [0; 20];
+                    writer.write_bytes(decimal(*self as u64, &mut buffer).as_bytes());
+                }
+            }
+        )+
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+
+macro_rules! impl_signed {
+    ($($type:ty),+) => {
+        $(
+            impl SyscallDisplay for $type {
+                fn write_to(&self, writer: &mut Writer) {
+                    if *self < 0 {
+                        writer.write_bytes(b"-");
+                    }
+                    let mut buffer = [0; 20];
+                    writer.write_bytes(decimal(self.unsigned_abs() as u64, &mut buffer).as_bytes());
+                }
+            }
+        )+
+    };
+}
+
+impl_signed!(i8, i16, i32, i64, isize);
+
+/// An integer printed in hexadecimal, `0x1f`.
+pub struct Hex(pub u64);
+
+impl SyscallDisplay for Hex {
+    fn write_to(&self, writer: &mut Writer) {
+        let mut buffer = [0; 16];
+        writer.write_bytes(b"0x");
+        writer.write_bytes(hex(self.0, &mut buffer).as_bytes());
+    }
+}
+
+impl<T: ?Sized> SyscallDisplay for *const T {
+    fn write_to(&self, writer: &mut Writer) {
+        Hex(self.addr() as u64).write_to(writer);
+    }
+}
+
+impl<T: ?Sized> SyscallDisplay for *mut T {
+    fn write_to(&self, writer: &mut Writer) {
+        Hex(self.addr() as u64).write_to(writer);
+    }
+}
+
+/// Formats `value` in decimal at the end of `buffer`, returning the digits.
+pub fn decimal(mut value: u64, buffer: &mut [u8; 20]) -> &str {
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            // SAFETY: Only ASCII digits.
+            return unsafe { core::str::from_utf8_unchecked(&buffer[start..]) };
+        }
+    }
+}
+
+/// Formats `value` in lowercase hexadecimal (without `0x`) at the end of `buffer`, returning the digits.
+pub fn hex(mut value: u64, buffer: &mut [u8; 16]) -> &str {
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        let digit = (value & 0xf) as u8;
+        buffer[start] = if digit < 10 {
+            b'0' + digit
+        } else {
+            b'a' + digit - 10
+        };
+        value >>= 4;
+        if value == 0 {
+            // SAFETY: Only ASCII digits.
+            return unsafe { core::str::from_utf8_unchecked(&buffer[start..]) };
+        }
+    }
+}
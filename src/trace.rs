@@ -0,0 +1,138 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write as _;
+
+use crate::{
+    arch::syscall::{self, O_CREAT, O_NOFOLLOW, O_TRUNC, O_WRONLY},
+    fs::File,
+    json,
+    linux::environment_variables::EnvironmentIter,
+    statistics,
+    sync::{Mutex, OnceLock},
+};
+
+/// Where `MIROS_TRACE` wants the trace written, unset if it doesn't.
+static TRACE_PATH: OnceLock<&'static str> = OnceLock::new();
+/// Every span that ended so far, written out all at once when we are done.
+static EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+
+/// What a span was spent on, the viewer colors and filters by these.
+///
+/// NOTE: There are no IFUNC resolution or constructor spans, we don't resolve `R_X86_64_IRELATIVE` in libraries or run
+/// initializers yet.
+pub const CATEGORY_SEARCH: &str = "search";
+pub const CATEGORY_MAPPING: &str = "mapping";
+pub const CATEGORY_RELOCATION: &str = "relocation";
+const CATEGORY_PHASE: &str = "phase";
+
+struct Event {
+    name: String,
+    category: &'static str,
+    start: u64,
+    end: u64,
+}
+
+/// A span of work, it ends (and is recorded) when dropped.
+pub struct Span(Option<Event>);
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(mut event) = self.0.take() {
+            event.end = statistics::now();
+            EVENTS.lock().push(event);
+        }
+    }
+}
+
+/// Reads `MIROS_TRACE`, the path to write a Chrome trace of startup to.
+pub fn initialize(environment: EnvironmentIter, secure: bool) {
+    // NOTE: Like `LD_DEBUG_OUTPUT`, in secure mode someone could use this to write files they can't otherwise touch.
+    if secure {
+        return;
+    }
+    for (name, value) in environment {
+        if name == "MIROS_TRACE" && !value.is_empty() {
+            let _ = TRACE_PATH.set(value);
+        }
+    }
+}
+
+pub fn is_enabled() -> bool {
+    TRACE_PATH.get().is_some()
+}
+
+/// Starts a span called `name`, it does nothing unless tracing is turned on.
+pub fn span(category: &'static str, name: &str) -> Span {
+    if !is_enabled() {
+        return Span(None);
+    }
+    Span(Some(Event {
+        name: name.to_string(),
+        category,
+        start: statistics::now(),
+        end: 0,
+    }))
+}
+
+/// Writes the trace in the Chrome trace event format, which both `chrome://tracing` and Perfetto open:
+///
+/// ```text
+/// {"traceEvents":[
+/// {"name":"libc.so.6","cat":"search","ph":"X","ts":12.345,"dur":8.100,"pid":1234,"tid":1234},
+/// ...
+/// ],"displayTimeUnit":"ns"}
+/// ```
+///
+/// The startup phases go on a track of their own, so the spans line up under them.
+pub fn write() {
+    let Some(path) = TRACE_PATH.get() else {
+        return;
+    };
+
+    let pid = syscall::getpid();
+    let mut output = String::from("{\"traceEvents\":[\n");
+    let _ = writeln!(
+        output,
+        "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{pid},\"tid\":0,\"args\":{{\"name\":\"startup phases\"}}}},",
+    );
+    let _ = write!(
+        output,
+        "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{pid},\"tid\":{pid},\"args\":{{\"name\":\"miros\"}}}}",
+    );
+    for (description, start, end) in statistics::phases() {
+        write_event(&mut output, description, CATEGORY_PHASE, start, end, 0);
+    }
+    for event in EVENTS.lock().iter() {
+        write_event(
+            &mut output,
+            &event.name,
+            event.category,
+            event.start,
+            event.end,
+            pid,
+        );
+    }
+    output.push_str("\n],\"displayTimeUnit\":\"ns\"}\n");
+
+    let file = File::open_with(path, O_WRONLY | O_CREAT | O_TRUNC | O_NOFOLLOW, 0o666);
+    if let Ok(file) = file {
+        let _ = file.write_all(output.as_bytes());
+    }
+}
+
+/// Appends a complete event (`"ph":"X"`), timestamps are in microseconds.
+fn write_event(output: &mut String, name: &str, category: &str, start: u64, end: u64, tid: u32) {
+    output.push_str(",\n{\"name\":");
+    json::push_string(output, name);
+    let _ = write!(
+        output,
+        ",\"cat\":\"{category}\",\"ph\":\"X\",\"ts\":{}.{:03},\"dur\":{}.{:03},\"pid\":{},\"tid\":{tid}}}",
+        start / 1000,
+        start % 1000,
+        end.saturating_sub(start) / 1000,
+        end.saturating_sub(start) % 1000,
+        syscall::getpid(),
+    );
+}